@@ -0,0 +1,20 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use pvl::{parse_keys_only, PvlDocument};
+use std::hint::black_box;
+
+const LABEL_PATH: &str = "tests/testdata/cassini/vims/v1883935188_1.lbl";
+
+fn bench_keys_only(c: &mut Criterion) {
+    let content = std::fs::read_to_string(LABEL_PATH).expect("Failed to load PVL label");
+    let mut group = c.benchmark_group("key_discovery");
+    group.bench_function("full_parse", |b| {
+        b.iter(|| black_box(PvlDocument::parse(&content).unwrap()))
+    });
+    group.bench_function("keys_only", |b| {
+        b.iter(|| black_box(parse_keys_only(&content).unwrap()))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_keys_only);
+criterion_main!(benches);
@@ -0,0 +1,33 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use pvl::PvlDocument;
+use std::fmt::Write;
+use std::hint::black_box;
+
+/// Builds a label with many independent top-level objects, as found in concatenated labels
+fn many_objects_label(objects: usize, keys: usize) -> String {
+    let mut label = String::from("PDS_VERSION_ID = PDS3\n");
+    for o in 0..objects {
+        writeln!(label, "OBJECT = TABLE_{}", o).unwrap();
+        for k in 0..keys {
+            writeln!(label, "  KEY_{} = ({}, {}.5, \"TEXT {}\")", k, k, o, k).unwrap();
+        }
+        writeln!(label, "END_OBJECT = TABLE_{}", o).unwrap();
+    }
+    label.push_str("END\n");
+    label
+}
+
+fn bench_parallel(c: &mut Criterion) {
+    let content = many_objects_label(500, 50);
+    let mut group = c.benchmark_group("many_objects");
+    group.bench_function("sequential", |b| {
+        b.iter(|| black_box(PvlDocument::parse(&content).unwrap()))
+    });
+    group.bench_function("parallel", |b| {
+        b.iter(|| black_box(PvlDocument::parse_parallel(&content).unwrap()))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_parallel);
+criterion_main!(benches);
@@ -0,0 +1,66 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use pvl::{PvlDocument, PvlReader};
+use std::fmt::Write;
+use std::hint::black_box;
+
+const LABEL_PATH: &str = "tests/testdata/msl/navcam/NRB_701384494RAD_F0933408NCAM00200M1.LBL";
+
+fn load_label() -> String {
+    std::fs::read_to_string(LABEL_PATH).expect("Failed to load PVL label")
+}
+
+/// Reads the key and value text of every line with the allocating String readers
+fn read_lines_owned(content: &str) {
+    let mut reader = PvlReader::new(content);
+    while !reader.is_eof() {
+        if let Ok(symbol) = reader.read_symbol() {
+            black_box(symbol);
+            black_box(reader.read_remaining_line().ok());
+        }
+        reader.skip_line().unwrap();
+    }
+}
+
+/// Reads the key and value text of every line with the zero-copy slice readers
+fn read_lines_slice(content: &str) {
+    let mut reader = PvlReader::new(content);
+    while !reader.is_eof() {
+        if let Ok(symbol) = reader.read_symbol_slice() {
+            black_box(symbol);
+            black_box(reader.read_remaining_line_slice().ok());
+        }
+        reader.skip_line().unwrap();
+    }
+}
+
+fn bench_line_readers(c: &mut Criterion) {
+    let content = load_label();
+    let mut group = c.benchmark_group("read_lines");
+    group.bench_function("owned", |b| b.iter(|| read_lines_owned(&content)));
+    group.bench_function("slice", |b| b.iter(|| read_lines_slice(&content)));
+    group.finish();
+}
+
+/// Builds a label whose values are wrapped onto continuation lines aligned past the `=`
+fn continued_values_label(keys: usize) -> String {
+    let mut label = String::new();
+    for k in 0..keys {
+        writeln!(label, "KEY_{:<31} = (1.0, 2.0, 3.0,", k).unwrap();
+        for _ in 0..3 {
+            writeln!(label, "{:37}4.0, 5.0, 6.0,", "").unwrap();
+        }
+        writeln!(label, "{:37}7.0)", "").unwrap();
+    }
+    label.push_str("END\n");
+    label
+}
+
+fn bench_continued_values(c: &mut Criterion) {
+    let content = continued_values_label(1000);
+    c.bench_function("continued_values", |b| {
+        b.iter(|| black_box(PvlDocument::parse(&content).unwrap()))
+    });
+}
+
+criterion_group!(benches, bench_line_readers, bench_continued_values);
+criterion_main!(benches);
@@ -0,0 +1,184 @@
+use crate::{
+    BlockKind, Error, KeyValuePair, PvlDocument, PvlGroup, PvlNode, Symbol, Value, ValueType,
+};
+
+/// Implements `From` for integer types, writing them as PVL integers
+macro_rules! impl_from_integer {
+    ($($type:ty),*) => {
+        $(
+            impl From<$type> for Value {
+                fn from(v: $type) -> Self {
+                    Value::new(&v.to_string())
+                }
+            }
+        )*
+    };
+}
+
+impl_from_integer!(i32, i64, u32, u64, usize);
+
+impl From<f64> for Value {
+    /// Written as by `Value::from_f64`, e.g. `1.0` or `1.0E-7`. Infinity and NaN have no PVL
+    /// form, so they're kept as `inf` or `NaN` with an undetermined type.
+    fn from(v: f64) -> Self {
+        Value::from_f64(v)
+            .unwrap_or_else(|_| Value::from_typed(&format!("{:?}", v), ValueType::Undetermined))
+    }
+}
+
+impl From<bool> for Value {
+    fn from(v: bool) -> Self {
+        Value::new(if v { "\"TRUE\"" } else { "\"FALSE\"" })
+    }
+}
+
+impl From<&str> for Value {
+    /// Written as a quoted string, with any embedded `"` doubled
+    fn from(v: &str) -> Self {
+        Value::new(&format!("\"{}\"", v.replace('"', "\"\"")))
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    /// Written as a sequence, e.g. `(1, 2, 3)`
+    fn from(v: Vec<T>) -> Self {
+        let elements = v
+            .into_iter()
+            .map(|e| e.into().to_pvl())
+            .collect::<Vec<String>>();
+        Value::new(&format!("({})", elements.join(", ")))
+    }
+}
+
+/// Constructs a PvlDocument in code
+/// # Example
+/// ```
+/// use pvl::PvlBuilder;
+///
+/// let doc = PvlBuilder::new()
+///     .key("LINES", 1024)
+///     .begin_group("IMAGE")
+///     .key("SAMPLES", 512)
+///     .end_group()
+///     .build()
+///     .unwrap();
+/// assert_eq!(doc.get("IMAGE.SAMPLES").unwrap().parse_u32().unwrap(), 512);
+/// ```
+#[derive(Debug)]
+pub struct PvlBuilder {
+    /// The top level document followed by each block that is still open
+    open: Vec<(Option<(BlockKind, String)>, PvlDocument)>,
+    /// First error encountered, reported by `build`
+    error: Option<Error>,
+}
+
+impl Default for PvlBuilder {
+    fn default() -> Self {
+        PvlBuilder::new()
+    }
+}
+
+impl PvlBuilder {
+    pub fn new() -> Self {
+        PvlBuilder {
+            open: vec![(None, PvlDocument::default())],
+            error: None,
+        }
+    }
+
+    /// Adds a `KEY = VALUE` pair to the innermost open block. Keys starting with `^` are
+    /// added as pointers.
+    pub fn key<V: Into<Value>>(mut self, key: &str, value: V) -> Self {
+        let key = if key.starts_with('^') {
+            Symbol::Pointer(key.to_owned())
+        } else {
+            Symbol::Key(key.to_owned())
+        };
+        self.current()
+            .nodes
+            .push(PvlNode::Pair(KeyValuePair::new(key, value.into())));
+        self
+    }
+
+    /// Opens a `GROUP = name` block. Following keys are added to it until `end_group`.
+    pub fn begin_group(self, name: &str) -> Self {
+        self.begin(BlockKind::Group, name)
+    }
+
+    /// Closes the innermost block, which must be a group
+    pub fn end_group(self) -> Self {
+        self.end(BlockKind::Group)
+    }
+
+    /// Opens an `OBJECT = name` block. Following keys are added to it until `end_object`.
+    pub fn begin_object(self, name: &str) -> Self {
+        self.begin(BlockKind::Object, name)
+    }
+
+    /// Closes the innermost block, which must be an object
+    pub fn end_object(self) -> Self {
+        self.end(BlockKind::Object)
+    }
+
+    /// Returns the built document, or an error if blocks weren't opened and closed in a
+    /// balanced way
+    pub fn build(mut self) -> Result<PvlDocument, Error> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        match self.open.pop() {
+            Some((None, document)) => Ok(document),
+            Some((Some((kind, name)), _)) => Err(Error::Syntax(format!(
+                "{} = {} is never closed",
+                kind.keyword(),
+                name
+            ))),
+            None => Err(Error::Programming("Builder has no document".to_owned())),
+        }
+    }
+
+    fn current(&mut self) -> &mut PvlDocument {
+        &mut self.open.last_mut().expect("Builder has no document").1
+    }
+
+    fn begin(mut self, kind: BlockKind, name: &str) -> Self {
+        self.open
+            .push((Some((kind, name.to_owned())), PvlDocument::default()));
+        self
+    }
+
+    fn end(mut self, kind: BlockKind) -> Self {
+        if self.error.is_some() {
+            return self;
+        }
+        match self.open.pop() {
+            Some((Some((opened, name)), document)) if opened == kind => {
+                self.current().nodes.push(PvlNode::Block(PvlGroup {
+                    kind,
+                    name,
+                    document,
+                    lines: (0, 0),
+                    span: 0..0,
+                }));
+            }
+            Some((Some((opened, name)), document)) => {
+                self.error = Some(Error::Syntax(format!(
+                    "{} closes {} = {}",
+                    kind.end_keyword(),
+                    opened.keyword(),
+                    name
+                )));
+                self.open.push((Some((opened, name)), document));
+            }
+            top => {
+                self.error = Some(Error::Syntax(format!(
+                    "{} without a matching {}",
+                    kind.end_keyword(),
+                    kind.keyword()
+                )));
+                self.open.extend(top);
+            }
+        }
+        self
+    }
+}
@@ -0,0 +1,203 @@
+use crate::{Error, Value, ValueType};
+use regex::Regex;
+use std::fmt;
+
+lazy_static! {
+    pub(crate) static ref DATETIME_DETERMINATE: Regex = Regex::new(
+        "^([0-9]{4})-(?:([0-9]{2})-([0-9]{2})|([0-9]{3}))T([0-9]{2}):([0-9]{2})(?::([0-9]{2})(?:\\.([0-9]+))?)?(Z|[+-][0-9]{2}(?::?[0-9]{2})?)?$"
+    )
+    .unwrap();
+    pub(crate) static ref DATE_DETERMINATE: Regex =
+        Regex::new("^([0-9]{4})-(?:([0-9]{2})-([0-9]{2})|([0-9]{3}))$").unwrap();
+    pub(crate) static ref TIME_DETERMINATE: Regex = Regex::new(
+        "^([0-9]{2}):([0-9]{2})(?::([0-9]{2})(?:\\.([0-9]+))?)?(Z|[+-][0-9]{2}(?::?[0-9]{2})?)?$"
+    )
+    .unwrap();
+}
+
+/// The date part of a PVL date/time, written either as a calendar date (`2019-06-20`) or as a
+/// day of the year (`2019-171`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Date {
+    Calendar { year: u32, month: u32, day: u32 },
+    DayOfYear { year: u32, day: u32 },
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Date::Calendar { year, month, day } => write!(f, "{:04}-{:02}-{:02}", year, month, day),
+            Date::DayOfYear { year, day } => write!(f, "{:04}-{:03}", year, day),
+        }
+    }
+}
+
+/// A PVL date/time such as `2019-06-20T12:34:56.789Z`. The fractional seconds, zone and any
+/// quotes are kept as written, so displaying it reproduces the source text with the same
+/// precision.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DateTime {
+    pub date: Date,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: Option<u32>,
+    /// Digits after the decimal point of the seconds, e.g. `789`
+    pub fraction: Option<String>,
+    /// `Z` or a UTC offset such as `+05:00`
+    pub zone: Option<String>,
+    /// Whether the source wrote it as a quoted string, e.g. `"2014-07-29T12:34:56Z"`
+    pub quoted: bool,
+}
+
+impl DateTime {
+    /// Returns the seconds including the fractional part
+    pub fn seconds_f64(&self) -> f64 {
+        let fraction = match &self.fraction {
+            Some(digits) => format!("0.{}", digits).parse::<f64>().unwrap_or(0.0),
+            None => 0.0,
+        };
+        self.second.unwrap_or(0) as f64 + fraction
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.quoted {
+            write!(f, "\"")?;
+        }
+        write!(f, "{}T{:02}:{:02}", self.date, self.hour, self.minute)?;
+        if let Some(second) = self.second {
+            write!(f, ":{:02}", second)?;
+            if let Some(fraction) = &self.fraction {
+                write!(f, ".{}", fraction)?;
+            }
+        }
+        if let Some(zone) = &self.zone {
+            write!(f, "{}", zone)?;
+        }
+        if self.quoted {
+            write!(f, "\"")?;
+        }
+        Ok(())
+    }
+}
+
+/// A PVL time of day without a date, such as `14:32:05`. Seconds default to 0 when the time
+/// is written as `HH:MM`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Time {
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    /// Digits after the decimal point of the seconds, e.g. `789`
+    pub fraction: Option<String>,
+    /// `Z` or a UTC offset such as `+05:00`
+    pub zone: Option<String>,
+}
+
+/// Parses the capture group `i` as a number, if it matched
+fn capture_number(caps: &regex::Captures, i: usize) -> Result<Option<u32>, Error> {
+    match caps.get(i) {
+        Some(m) => m
+            .as_str()
+            .parse::<u32>()
+            .map(Some)
+            .map_err(|_| Error::ValueTypeParseError),
+        None => Ok(None),
+    }
+}
+
+impl Value {
+    /// Returns the trimmed text of a value detected as the given date or time type (or not
+    /// detected at all), or of a quoted string without its quotes
+    fn temporal_text(&self, value_type: ValueType) -> Result<&str, Error> {
+        if self.value_type == value_type || self.value_type == ValueType::Undetermined {
+            Ok(self.value_raw.trim())
+        } else if self.value_type == ValueType::String {
+            Ok(self.value_raw.trim().trim_matches('"').trim())
+        } else {
+            Err(Error::InvalidType)
+        }
+    }
+
+    /// Parses a date/time of the form `YYYY-MM-DDThh:mm[:ss[.fff]][Z]` or
+    /// `YYYY-DDDThh:mm[:ss[.fff]][Z]`. A date/time written as a quoted string, e.g.
+    /// `"2014-07-29T12:34:56Z"`, parses to the same fields as the unquoted form, with `quoted`
+    /// set.
+    /// # Example
+    /// ```
+    /// use pvl::Value;
+    ///
+    /// let dt = Value::new("2019-06-20T12:34:56.789Z").parse_datetime().unwrap();
+    /// assert_eq!(dt.fraction.as_deref(), Some("789"));
+    /// assert_eq!(dt.to_string(), "2019-06-20T12:34:56.789Z");
+    /// ```
+    pub fn parse_datetime(&self) -> Result<DateTime, Error> {
+        let caps = DATETIME_DETERMINATE
+            .captures(self.temporal_text(ValueType::DateTime)?)
+            .ok_or(Error::ValueTypeParseError)?;
+        let number = |i: usize| capture_number(&caps, i);
+
+        let year = number(1)?.ok_or(Error::ValueTypeParseError)?;
+        let date = match (number(2)?, number(3)?, number(4)?) {
+            (Some(month), Some(day), _) => Date::Calendar { year, month, day },
+            (_, _, Some(day)) => Date::DayOfYear { year, day },
+            _ => return Err(Error::ValueTypeParseError),
+        };
+        Ok(DateTime {
+            date,
+            hour: number(5)?.ok_or(Error::ValueTypeParseError)?,
+            minute: number(6)?.ok_or(Error::ValueTypeParseError)?,
+            second: number(7)?,
+            fraction: caps.get(8).map(|m| m.as_str().to_owned()),
+            zone: caps.get(9).map(|m| m.as_str().to_owned()),
+            quoted: self.value_type == ValueType::String,
+        })
+    }
+
+    /// Parses a date without a time, either a calendar date (`YYYY-MM-DD`) or a day of the year
+    /// (`YYYY-DDD`). Quoted dates are accepted as with `parse_datetime`.
+    /// # Example
+    /// ```
+    /// use pvl::{Date, Value};
+    ///
+    /// let date = Value::new("2014-210").parse_date().unwrap();
+    /// assert_eq!(date, Date::DayOfYear { year: 2014, day: 210 });
+    /// ```
+    pub fn parse_date(&self) -> Result<Date, Error> {
+        let caps = DATE_DETERMINATE
+            .captures(self.temporal_text(ValueType::Date)?)
+            .ok_or(Error::ValueTypeParseError)?;
+        let number = |i: usize| capture_number(&caps, i);
+
+        let year = number(1)?.ok_or(Error::ValueTypeParseError)?;
+        match (number(2)?, number(3)?, number(4)?) {
+            (Some(month), Some(day), _) => Ok(Date::Calendar { year, month, day }),
+            (_, _, Some(day)) => Ok(Date::DayOfYear { year, day }),
+            _ => Err(Error::ValueTypeParseError),
+        }
+    }
+
+    /// Parses a time of day of the form `hh:mm[:ss[.fff]][Z]`. Seconds are 0 if omitted. Quoted
+    /// times are accepted as with `parse_datetime`.
+    /// # Example
+    /// ```
+    /// use pvl::Value;
+    ///
+    /// let t = Value::new("14:32").parse_time().unwrap();
+    /// assert_eq!((t.hour, t.minute, t.second), (14, 32, 0));
+    /// ```
+    pub fn parse_time(&self) -> Result<Time, Error> {
+        let caps = TIME_DETERMINATE
+            .captures(self.temporal_text(ValueType::Time)?)
+            .ok_or(Error::ValueTypeParseError)?;
+        let number = |i: usize| capture_number(&caps, i);
+        Ok(Time {
+            hour: number(1)?.ok_or(Error::ValueTypeParseError)?,
+            minute: number(2)?.ok_or(Error::ValueTypeParseError)?,
+            second: number(3)?.unwrap_or(0),
+            fraction: caps.get(4).map(|m| m.as_str().to_owned()),
+            zone: caps.get(5).map(|m| m.as_str().to_owned()),
+        })
+    }
+}
@@ -0,0 +1,372 @@
+use crate::ser::named_nodes;
+use crate::{Error, PvlDocument, PvlNode, Value, ValueType};
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor,
+};
+use serde::{forward_to_deserialize_any, Deserializer};
+use std::fmt;
+
+/// Error raised while deserializing. Converted to `Error::General` once deserialization ends.
+#[derive(Debug)]
+struct DeError(String);
+
+impl fmt::Display for DeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for DeError {}
+
+impl de::Error for DeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        DeError(msg.to_string())
+    }
+}
+
+/// Deserializes a document into any type implementing `Deserialize`. Groups and objects map to
+/// nested structs (or maps) and keys to fields, using the same names as in the label. Scalars
+/// are converted following their detected `ValueType`. A key that may repeat can be read into
+/// a `Vec`, even when it only appears once.
+/// # Example
+/// ```
+/// use pvl::{from_document, PvlDocument};
+/// use std::collections::HashMap;
+///
+/// let doc = PvlDocument::parse("LINES = 1024\nSAMPLES = 512\nEND\n").unwrap();
+/// let dims: HashMap<String, u32> = from_document(&doc).unwrap();
+/// assert_eq!(dims["SAMPLES"], 512);
+/// ```
+pub fn from_document<T: DeserializeOwned>(doc: &PvlDocument) -> Result<T, Error> {
+    T::deserialize(DocumentDeserializer(doc)).map_err(|why| Error::General(why.0))
+}
+
+struct DocumentDeserializer<'a>(&'a PvlDocument);
+
+impl<'de> Deserializer<'de> for DocumentDeserializer<'_> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_map(DocumentMap {
+            entries: named_nodes(self.0).into_iter(),
+            pending: None,
+        })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct DocumentMap<'a> {
+    entries: std::vec::IntoIter<(&'a str, Vec<&'a PvlNode>)>,
+    pending: Option<Vec<&'a PvlNode>>,
+}
+
+impl<'de> MapAccess<'de> for DocumentMap<'_> {
+    type Error = DeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        match self.entries.next() {
+            Some((name, nodes)) => {
+                self.pending = Some(nodes);
+                seed.deserialize(de::value::StrDeserializer::<DeError>::new(name))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.pending.take() {
+            Some(nodes) => seed.deserialize(NodesDeserializer(nodes)),
+            None => Err(de::Error::custom("Value requested before its key")),
+        }
+    }
+}
+
+/// Every node sharing a name. A single node deserializes as itself, several as a sequence.
+struct NodesDeserializer<'a>(Vec<&'a PvlNode>);
+
+fn node_deserializer(node: &PvlNode) -> NodeDeserializer<'_> {
+    match node {
+        PvlNode::Pair(kvp) => NodeDeserializer::Value(kvp.value.clone()),
+        PvlNode::Block(g) => NodeDeserializer::Block(&g.document),
+    }
+}
+
+impl<'de> Deserializer<'de> for NodesDeserializer<'_> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0.as_slice() {
+            [single] => node_deserializer(single).deserialize_any(visitor),
+            _ => visitor.visit_seq(NodeSeq {
+                nodes: self.0.into_iter(),
+            }),
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0.as_slice() {
+            // A single array value fills the sequence with its elements
+            [single] => node_deserializer(single).deserialize_seq(visitor),
+            _ => visitor.visit_seq(NodeSeq {
+                nodes: self.0.into_iter(),
+            }),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0.as_slice() {
+            [single] => node_deserializer(single).deserialize_str(visitor),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0.as_slice() {
+            [single] => node_deserializer(single).deserialize_bool(visitor),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.0.as_slice() {
+            [single] => node_deserializer(single).deserialize_enum(name, variants, visitor),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char
+        bytes byte_buf unit unit_struct tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct NodeSeq<'a> {
+    nodes: std::vec::IntoIter<&'a PvlNode>,
+}
+
+impl<'de> SeqAccess<'de> for NodeSeq<'_> {
+    type Error = DeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        match self.nodes.next() {
+            Some(node) => seed.deserialize(node_deserializer(node)).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+enum NodeDeserializer<'a> {
+    Value(Value),
+    Block(&'a PvlDocument),
+}
+
+impl<'de> Deserializer<'de> for NodeDeserializer<'_> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            NodeDeserializer::Value(v) => ValueDeserializer(v).deserialize_any(visitor),
+            NodeDeserializer::Block(d) => DocumentDeserializer(d).deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            NodeDeserializer::Value(v) => ValueDeserializer(v).deserialize_seq(visitor),
+            NodeDeserializer::Block(d) => DocumentDeserializer(d).deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            NodeDeserializer::Value(v) => ValueDeserializer(v).deserialize_str(visitor),
+            NodeDeserializer::Block(d) => DocumentDeserializer(d).deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            NodeDeserializer::Value(v) => ValueDeserializer(v).deserialize_bool(visitor),
+            NodeDeserializer::Block(d) => DocumentDeserializer(d).deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self {
+            NodeDeserializer::Value(v) => {
+                ValueDeserializer(v).deserialize_enum(name, variants, visitor)
+            }
+            NodeDeserializer::Block(d) => DocumentDeserializer(d).deserialize_any(visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char
+        bytes byte_buf unit unit_struct newtype_struct tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// Deserializes a single value, following its detected `ValueType`
+struct ValueDeserializer(Value);
+
+impl ValueDeserializer {
+    fn invalid<T>(&self, expected: &str) -> Result<T, DeError> {
+        Err(de::Error::custom(format!(
+            "Cannot read {} as {}",
+            self.0.to_pvl(),
+            expected
+        )))
+    }
+
+    /// The value as text, unquoted where it was a string and as written otherwise
+    fn text(self) -> String {
+        match self.0.value_type {
+            ValueType::Flag => self.0.parse_flag(),
+            _ => self.0.parse_string(),
+        }
+        .unwrap_or_else(|_| self.0.to_pvl())
+    }
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let v = &self.0;
+        match v.value_type {
+            ValueType::Integer => match v.parse_i64() {
+                Ok(i) => visitor.visit_i64(i),
+                Err(_) => self.invalid("an integer"),
+            },
+            ValueType::Float => match v.parse_f64() {
+                Ok(f) => visitor.visit_f64(f),
+                Err(_) => self.invalid("a float"),
+            },
+            ValueType::Bool => match v.as_bool() {
+                Ok(b) => visitor.visit_bool(b),
+                Err(_) => self.invalid("a boolean"),
+            },
+            ValueType::Array => self.deserialize_seq(visitor),
+            _ => self.deserialize_str(visitor),
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        // A lone scalar reads as a sequence of one, as a key that may be repeated would
+        let elements = if self.0.value_type == ValueType::Array {
+            match self.0.parse_array() {
+                Ok(elements) => elements,
+                Err(_) => return self.invalid("an array"),
+            }
+        } else {
+            vec![self.0]
+        };
+        visitor.visit_seq(ValueSeq {
+            elements: elements.into_iter(),
+        })
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_string(self.text())
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0.as_bool() {
+            Ok(b) => visitor.visit_bool(b),
+            Err(_) => self.invalid("a boolean"),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        // Only unit variants can be written in PVL, named by a flag or a string
+        visitor.visit_enum(self.text().into_deserializer())
+    }
+
+    forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char
+        bytes byte_buf unit unit_struct newtype_struct tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct ValueSeq {
+    elements: std::vec::IntoIter<Value>,
+}
+
+impl<'de> SeqAccess<'de> for ValueSeq {
+    type Error = DeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        match self.elements.next() {
+            Some(e) => seed.deserialize(ValueDeserializer(e)).map(Some),
+            None => Ok(None),
+        }
+    }
+}
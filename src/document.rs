@@ -0,0 +1,1002 @@
+use crate::{
+    comments_within, fnv1a_64, is_quote_closed, open_brackets, strip_trailing_comment, Error,
+    KeyValuePair, PvlReader, Symbol, Value, ValueType,
+};
+use std::{collections::HashMap, fs, ops::Range, path::Path};
+
+/// The keyword that opened a PvlGroup block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockKind {
+    Group,
+    Object,
+}
+
+impl BlockKind {
+    /// The keyword opening a block of this kind
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            BlockKind::Group => "GROUP",
+            BlockKind::Object => "OBJECT",
+        }
+    }
+
+    /// The keyword closing a block of this kind
+    pub fn end_keyword(&self) -> &'static str {
+        match self {
+            BlockKind::Group => "END_GROUP",
+            BlockKind::Object => "END_OBJECT",
+        }
+    }
+}
+
+/// How `PvlDocument::merge_with` treats a key present in both documents
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergeMode {
+    /// Every occurrence of the key is replaced by the other document's occurrences, at the
+    /// position of the first one
+    #[default]
+    Replace,
+    /// The other document's occurrences are added after the existing ones, as a label that
+    /// repeats a key (e.g. `NOTE`) would have them
+    Append,
+}
+
+/// A difference between two documents, as returned by `PvlDocument::diff`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// A key only in the other document
+    Added { key_path: String, value: Value },
+    /// A key only in this document
+    Removed { key_path: String, value: Value },
+    /// A key in both documents with different values
+    Modified {
+        key_path: String,
+        old: Value,
+        new: Value,
+    },
+}
+
+/// Represents a GROUP...END_GROUP or OBJECT...END_OBJECT block within a PvlDocument
+#[derive(Debug, Clone)]
+pub struct PvlGroup {
+    pub kind: BlockKind,
+    pub name: String,
+    pub document: PvlDocument,
+    /// 1-based source lines of the opening and closing statements. `(0, 0)` for blocks that
+    /// weren't parsed from text.
+    pub(crate) lines: (usize, usize),
+    /// Byte range of the block in the source, from the start of its opening line through the
+    /// end of its closing statement. Empty for blocks that weren't parsed from text.
+    pub(crate) span: Range<usize>,
+}
+
+impl PvlGroup {
+    /// Returns the 1-based source lines of the block's opening (`GROUP = ...`) and closing
+    /// (`END_GROUP = ...`) statements. Both are 0 if the block wasn't parsed from text.
+    pub fn lines(&self) -> (usize, usize) {
+        self.lines
+    }
+
+    /// Returns the byte range of the whole block in the source text, from the start of its
+    /// opening line (including any indentation) to the end of its closing statement, excluding
+    /// the line break. `&content[group.span()]` extracts the block's source. Empty if the block
+    /// wasn't parsed from text.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+}
+
+/// A single entry of a PvlDocument
+#[derive(Debug, Clone)]
+pub enum PvlNode {
+    Pair(KeyValuePair),
+    Block(PvlGroup),
+}
+
+/// Tree representation of a PVL label. Unlike `Pvl`, blocks may be nested within each other
+/// and entries are kept in source order.
+#[derive(Debug, Clone, Default)]
+pub struct PvlDocument {
+    pub nodes: Vec<PvlNode>,
+    /// Optional name of the root, used as the first segment of `prefix_keys`. Never written out.
+    pub(crate) name: Option<String>,
+    /// Byte offset into the source text just past the `END` line, if the label had one
+    pub(crate) label_end: Option<usize>,
+}
+
+/// Extracts the name of a GROUP/OBJECT from its opening (or closing) statement
+fn block_name(kvp: &KeyValuePair) -> String {
+    kvp.value.to_pvl().trim().trim_matches('"').to_owned()
+}
+
+/// Splits a path such as `IMAGE/LINES` or `IMAGE.LINES` into its segments
+fn split_path(path: &str) -> Vec<&str> {
+    path.split(['/', '.']).filter(|s| !s.is_empty()).collect()
+}
+
+/// Compares two key or block names, optionally ignoring ASCII case
+fn names_match(a: &str, b: &str, ignore_case: bool) -> bool {
+    if ignore_case {
+        a.eq_ignore_ascii_case(b)
+    } else {
+        a == b
+    }
+}
+
+/// Returns the key name of a KEY = VALUE or ^POINTER = VALUE pair
+fn key_name(kvp: &KeyValuePair) -> Option<&str> {
+    match &kvp.key {
+        Symbol::Key(k) | Symbol::Pointer(k) => Some(k),
+        _ => None,
+    }
+}
+
+/// Indexes the pairs from `PvlDocument::numbered_pairs` by path and occurrence
+fn index_numbered<'a>(
+    pairs: &'a [(String, usize, &'a Value)],
+) -> HashMap<(&'a str, usize), &'a Value> {
+    pairs
+        .iter()
+        .map(|(p, n, v)| ((p.as_str(), *n), *v))
+        .collect()
+}
+
+/// Whether two values are the same apart from their whitespace, such as where an array was
+/// wrapped onto more lines. Array elements are compared one by one.
+fn values_match(a: &Value, b: &Value) -> bool {
+    let (a_raw, b_raw) = (a.as_str().trim(), b.as_str().trim());
+    match (a.parse_array(), b.parse_array()) {
+        (Ok(x), Ok(y)) => {
+            a_raw[..1] == b_raw[..1]
+                && x.len() == y.len()
+                && x.iter().zip(y.iter()).all(|(x, y)| values_match(x, y))
+        }
+        _ => a.value_type() == b.value_type() && a_raw == b_raw,
+    }
+}
+
+/// Lists the dotted paths of every key in a label without parsing (or type checking) any
+/// values, which is considerably cheaper than a full parse when only the schema is needed
+/// # Example
+/// ```
+/// use pvl::parse_keys_only;
+///
+/// let keys = parse_keys_only("GROUP = IMAGE\n  LINES = 1024\nEND_GROUP = IMAGE\nEND\n").unwrap();
+/// assert_eq!(keys, vec!["IMAGE.LINES"]);
+/// ```
+pub fn parse_keys_only(content: &str) -> Result<Vec<String>, Error> {
+    let mut reader = PvlReader::new(content);
+    let mut blocks: Vec<String> = vec![];
+    let mut keys = vec![];
+
+    while reader.skip_to_statement()? {
+        if reader.is_at_value_line_continuation()? {
+            reader.skip_line()?;
+            continue;
+        }
+        match strip_trailing_comment(reader.read_symbol_slice()?) {
+            "END" => break,
+            "GROUP" | "OBJECT" => {
+                let name = strip_trailing_comment(reader.read_remaining_line_slice()?)
+                    .trim_matches('"')
+                    .to_owned();
+                blocks.push(name);
+            }
+            "END_GROUP" | "END_OBJECT" => {
+                blocks.pop();
+            }
+            key => {
+                blocks.push(key.to_owned());
+                keys.push(blocks.join("."));
+                blocks.pop();
+
+                let assigned =
+                    !reader.is_eof() && reader.current_char()? == reader.assignment_char();
+                let mut quoted = reader.read_remaining_line_slice()?.to_owned();
+                // Skip over a value that starts on a later line
+                if quoted.is_empty() && assigned {
+                    if let Some(line) = reader.value_line_after(reader.pos + 1) {
+                        quoted = reader.content[line.clone()].to_owned();
+                        reader.pos = line.end;
+                    }
+                }
+
+                // Skip over the remaining lines of a multi-line quoted string
+                while !is_quote_closed(&quoted) && !reader.is_eof() {
+                    reader.skip_line()?;
+                    quoted.push_str(reader.read_remaining_line_slice().unwrap_or_default());
+                }
+
+                // Skip over the remaining lines of a sequence or set
+                while open_brackets(&quoted) > 0 {
+                    let Some(line) = reader.value_line_after(reader.pos + 1) else {
+                        break;
+                    };
+                    quoted.push_str(&reader.content[line.clone()]);
+                    reader.pos = line.end;
+                }
+            }
+        }
+        reader.skip_line()?;
+    }
+    Ok(keys)
+}
+
+impl PvlReader<'_> {
+    /// Parses the content from the current caret position through `END` into a PvlDocument tree
+    pub fn parse_document(&mut self) -> Result<PvlDocument, Error> {
+        let start = self.pos;
+        let (document, _, _) = self.read_document_block(None, 0)?;
+        if self.options().compute_hash {
+            let end = self.statement_end().max(start);
+            self.content_hash = Some(fnv1a_64(&self.content.as_bytes()[start..end]));
+        }
+        Ok(document)
+    }
+
+    /// Same as `parse_document`, but gives the document's root the name, e.g. the product the
+    /// label describes, for prefixing its keys
+    /// # Example
+    /// ```
+    /// use pvl::PvlReader;
+    ///
+    /// let doc = PvlReader::new("LINES = 1024\nEND\n").parse_document_named("N_001").unwrap();
+    /// assert_eq!(doc.prefix_keys(), vec!["N_001.LINES"]);
+    /// ```
+    pub fn parse_document_named(&mut self, name: &str) -> Result<PvlDocument, Error> {
+        let mut document = self.parse_document()?;
+        document.name = Some(name.to_owned());
+        Ok(document)
+    }
+
+    /// Reads statements into a document until the terminator of the enclosing block is found.
+    /// `opener` is `None` for the top level of the label, at a `depth` of 0. Also returns the
+    /// line of the terminator and the byte offset its statement ends at.
+    fn read_document_block(
+        &mut self,
+        opener: Option<&KeyValuePair>,
+        depth: usize,
+    ) -> Result<(PvlDocument, usize, usize), Error> {
+        if depth > self.options().max_depth {
+            return Err(Error::Syntax("max nesting depth exceeded".to_owned()));
+        }
+        let mut document = PvlDocument::default();
+
+        let mut previous_end = self.pos;
+        while self.skip_to_statement()? {
+            let start = self.pos;
+            let mut kvp = match self.read_key_value_pair_raw() {
+                Ok(kvp) => kvp,
+                Err(why) => {
+                    self.recover(start, why)?;
+                    continue;
+                }
+            };
+            match (&kvp.key, opener.map(|o| &o.key)) {
+                (Symbol::End, _) => {
+                    // Binary data may follow immediately, so only the END line's own line
+                    // break is taken
+                    let end = self.statement_end();
+                    let end = end + usize::from(self.content[end..].starts_with('\n'));
+                    document.label_end = Some(self.source_offset(end));
+                    break;
+                }
+                (Symbol::Group, _) | (Symbol::Object, _) => {
+                    let start_line = self.line_of(start);
+                    let kind = if kvp.key == Symbol::Group {
+                        BlockKind::Group
+                    } else {
+                        BlockKind::Object
+                    };
+                    let mut name = block_name(&kvp);
+                    if name.is_empty() {
+                        if !self.is_lenient() {
+                            let why = Error::Syntax(format!("{} without a name", kind.keyword()));
+                            self.recover(start, why)?;
+                        }
+                        name = format!("UNNAMED_{}_{}", kind.keyword(), start_line);
+                    }
+                    let (child, end_line, end) = self.read_document_block(Some(&kvp), depth + 1)?;
+                    document.nodes.push(PvlNode::Block(PvlGroup {
+                        kind,
+                        name,
+                        document: child,
+                        lines: (start_line, end_line),
+                        span: start..end,
+                    }));
+                }
+                (Symbol::GroupEnd, Some(Symbol::Group))
+                | (Symbol::ObjectEnd, Some(Symbol::Object)) => {
+                    // The name on the terminator is optional, but must match when given. Any
+                    // name closes a block that had none.
+                    let name = block_name(&kvp);
+                    if let Some(o) = opener.filter(|o| {
+                        !name.is_empty() && !block_name(o).is_empty() && name != block_name(o)
+                    }) {
+                        let why = Error::Syntax(format!(
+                            "{:?} = {} doesn't match {:?} = {}",
+                            kvp.key,
+                            name,
+                            o.key,
+                            block_name(o)
+                        ));
+                        // Recovered from by closing the block anyway
+                        self.recover(start, why)?;
+                    }
+                    return Ok((document, self.line_of(start), self.statement_end()));
+                }
+                // A line skipped by the reader
+                (Symbol::BlankLine, _) => {}
+                (Symbol::GroupEnd, _) | (Symbol::ObjectEnd, _) => {
+                    let why =
+                        Error::Syntax(format!("Unexpected {:?} = {}", kvp.key, block_name(&kvp)));
+                    self.recover(start, why)?;
+                }
+                _ => {
+                    if self.options().error_on_undetermined
+                        && kvp.value.value_type() == ValueType::Undetermined
+                    {
+                        let why = Error::Syntax(format!(
+                            "Undetermined value for {:?} = {}",
+                            kvp.key,
+                            kvp.value.as_str()
+                        ));
+                        self.recover(start, why)?;
+                    } else if self.collected_errors.is_some()
+                        && !self.statement_has_assignment(start)
+                    {
+                        // Only reported when collecting errors, as a plain parse has always
+                        // read such a line as a key with an empty value
+                        let why = Error::Syntax(format!("Missing '=' after {:?}", kvp.key));
+                        self.recover(start, why)?;
+                    } else {
+                        if self.options().detect_ranges && kvp.value.is_range_syntax() {
+                            kvp.value = Value::from_typed(kvp.value.as_str(), ValueType::Range);
+                        }
+                        if self.options().capture_comments {
+                            kvp.comments = comments_within(&self.content[previous_end..start]);
+                        }
+                        document.nodes.push(PvlNode::Pair(kvp))
+                    }
+                }
+            }
+            previous_end = self.pos;
+        }
+
+        if let Some(o) = opener {
+            let why = Error::Syntax(format!(
+                "Missing terminator for {:?} = {}",
+                o.key,
+                block_name(o)
+            ));
+            self.recover(self.pos, why)?;
+        }
+        Ok((document, self.line_number(), self.statement_end()))
+    }
+
+    /// Records a malformed statement starting at the given byte position when errors are being
+    /// collected, making sure the caret has moved past it. Otherwise the error is returned.
+    fn recover(&mut self, at: usize, why: Error) -> Result<(), Error> {
+        let line = self.line_of(at);
+        let Some(errors) = self.collected_errors.as_mut() else {
+            return Err(why);
+        };
+        errors.push(match why {
+            Error::Syntax(s) | Error::General(s) => Error::Syntax(format!("Line {}: {}", line, s)),
+            other => Error::Syntax(format!("Line {}: {:?}", line, other)),
+        });
+        if self.pos == at {
+            self.skip_line()?;
+        }
+        Ok(())
+    }
+
+    /// Whether the statement starting at the given byte position has an `=` on its first line
+    fn statement_has_assignment(&self, at: usize) -> bool {
+        self.content[at..]
+            .lines()
+            .next()
+            .is_some_and(|line| line.contains(self.assignment_char()))
+    }
+}
+
+impl PvlDocument {
+    /// Parses the contents of a supplied PVL-formatted String into a document tree
+    /// # Example
+    /// ```
+    /// use pvl::PvlDocument;
+    ///
+    /// let doc = PvlDocument::parse("GROUP = IMAGE\n  LINES = 1024\nEND_GROUP = IMAGE\nEND\n").unwrap();
+    /// assert_eq!(doc.get("IMAGE/LINES").unwrap().parse_u32().unwrap(), 1024);
+    /// ```
+    pub fn parse(content: &str) -> Result<Self, Error> {
+        PvlReader::new(content).parse_document()
+    }
+
+    /// Parses as much of a label as possible, skipping malformed statements instead of failing
+    /// on the first one. Returns the document of every statement that could be read, along with
+    /// an `Error::Syntax` naming the line of each problem. Stray `=` lines are recovered from as
+    /// with `PvlReader::set_lenient`.
+    /// # Example
+    /// ```
+    /// use pvl::PvlDocument;
+    ///
+    /// let (doc, errors) = PvlDocument::parse_lenient("A = 1\nEND_GROUP = X\nB = 2\nEND\n");
+    /// assert!(doc.get("B").is_some());
+    /// assert_eq!(errors.len(), 1);
+    /// ```
+    pub fn parse_lenient(content: &str) -> (Self, Vec<Error>) {
+        let mut reader = PvlReader::new(content);
+        reader.set_lenient(true);
+        reader.collected_errors = Some(vec![]);
+        let document = reader.parse_document();
+        let mut errors = reader.collected_errors.take().unwrap_or_default();
+        match document {
+            Ok(document) => (document, errors),
+            Err(why) => {
+                errors.push(why);
+                (PvlDocument::default(), errors)
+            }
+        }
+    }
+
+    /// Reads and parses a PVL label file. Attached labels are fine, as anything after `END` is
+    /// ignored.
+    /// # Example
+    /// ```
+    /// use pvl::PvlDocument;
+    ///
+    /// let doc = PvlDocument::from_file("tests/testdata/cassini/vims/v1883935188_1.lbl").unwrap();
+    /// assert!(doc.get("SPECTRAL_QUBE/CORE_ITEMS").is_some());
+    /// ```
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let bytes = fs::read(path).map_err(Error::Io)?;
+        PvlDocument::parse(&String::from_utf8_lossy(&bytes))
+    }
+
+    /// Returns the byte offset of the data that follows an attached label. That is
+    /// `LABEL_RECORDS * RECORD_BYTES` when the label declares both, otherwise the offset just
+    /// past the `END` line rounded up to a whole number of `RECORD_BYTES` (if given). `None` if
+    /// the document wasn't read from text with an `END`.
+    /// # Example
+    /// ```
+    /// use pvl::PvlDocument;
+    ///
+    /// let doc = PvlDocument::parse("RECORD_BYTES = 16\nLINES = 2\nEND\n").unwrap();
+    /// assert_eq!(doc.data_offset(), Some(32));
+    /// ```
+    pub fn data_offset(&self) -> Option<usize> {
+        let record_bytes = self
+            .get("RECORD_BYTES")
+            .and_then(|v| v.parse_usize().ok())
+            .filter(|r| *r > 0);
+        let label_records = self.get("LABEL_RECORDS").and_then(|v| v.parse_usize().ok());
+        match (record_bytes, label_records) {
+            (Some(r), Some(l)) => r.checked_mul(l),
+            (Some(r), None) => self.label_end?.checked_next_multiple_of(r),
+            (None, _) => self.label_end,
+        }
+    }
+
+    /// Returns the first key/value pair at this level with the given key name
+    fn get_pair(&self, name: &str, ignore_case: bool) -> Option<&KeyValuePair> {
+        self.nodes.iter().find_map(|n| match n {
+            PvlNode::Pair(kvp)
+                if key_name(kvp).is_some_and(|k| names_match(k, name, ignore_case)) =>
+            {
+                Some(kvp)
+            }
+            _ => None,
+        })
+    }
+
+    /// Visits every key/value pair in the tree in source order along with the names of the
+    /// blocks enclosing it and its own key
+    fn for_each_pair<'a>(
+        &'a self,
+        path: &mut Vec<&'a str>,
+        f: &mut dyn FnMut(&[&'a str], &'a KeyValuePair),
+    ) {
+        for node in self.nodes.iter() {
+            match node {
+                PvlNode::Pair(kvp) => {
+                    if let Some(k) = key_name(kvp) {
+                        path.push(k);
+                        f(path, kvp);
+                        path.pop();
+                    }
+                }
+                PvlNode::Block(g) => {
+                    path.push(&g.name);
+                    g.document.for_each_pair(path, f);
+                    path.pop();
+                }
+            }
+        }
+    }
+
+    /// Collects every block in the tree in source order, parents before their children, along
+    /// with its dotted path
+    fn collect_blocks<'a>(&'a self, prefix: &str, blocks: &mut Vec<(String, &'a PvlGroup)>) {
+        for node in self.nodes.iter() {
+            if let PvlNode::Block(g) = node {
+                let path = if prefix.is_empty() {
+                    g.name.to_owned()
+                } else {
+                    format!("{}.{}", prefix, g.name)
+                };
+                blocks.push((path.clone(), g));
+                g.document.collect_blocks(&path, blocks);
+            }
+        }
+    }
+
+    /// Returns the first GROUP/OBJECT block at this level with the given name
+    fn get_block(&self, name: &str, ignore_case: bool) -> Option<&PvlGroup> {
+        self.nodes.iter().find_map(|n| match n {
+            PvlNode::Block(g) if names_match(&g.name, name, ignore_case) => Some(g),
+            _ => None,
+        })
+    }
+
+    /// Returns the contents of the named block at this level, creating an empty GROUP for it if
+    /// it doesn't exist yet
+    fn block_or_insert(&mut self, name: &str) -> &mut PvlDocument {
+        let index = match self
+            .nodes
+            .iter()
+            .position(|n| matches!(n, PvlNode::Block(g) if g.name == name))
+        {
+            Some(i) => i,
+            None => {
+                self.nodes.push(PvlNode::Block(PvlGroup {
+                    kind: BlockKind::Group,
+                    name: name.to_owned(),
+                    document: PvlDocument::default(),
+                    lines: (0, 0),
+                    span: 0..0,
+                }));
+                self.nodes.len() - 1
+            }
+        };
+        match &mut self.nodes[index] {
+            PvlNode::Block(g) => &mut g.document,
+            PvlNode::Pair(_) => unreachable!(),
+        }
+    }
+
+    /// Sets the value at the given path, creating any missing groups along the way, so
+    /// `insert("IMAGE.STATS.MEAN", v)` builds the `IMAGE` and `STATS` groups if absent. An
+    /// existing value at the path is replaced.
+    pub fn insert(&mut self, path: &str, value: Value) -> Result<(), Error> {
+        let segments = split_path(path);
+        let Some((key, parents)) = segments.split_last() else {
+            return Err(Error::General(format!("Invalid key path '{}'", path)));
+        };
+        let mut document = self;
+        for parent in parents {
+            document = document.block_or_insert(parent);
+        }
+
+        let existing = document.nodes.iter_mut().find_map(|n| match n {
+            PvlNode::Pair(kvp) if key_name(kvp) == Some(key) => Some(kvp),
+            _ => None,
+        });
+        match existing {
+            Some(kvp) => kvp.value = value,
+            None => {
+                let symbol = if key.starts_with('^') {
+                    Symbol::Pointer(key.to_string())
+                } else {
+                    Symbol::Key(key.to_string())
+                };
+                document
+                    .nodes
+                    .push(PvlNode::Pair(KeyValuePair::new(symbol, value)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Walks the path down through nested blocks to the first matching value
+    fn lookup(&self, path: &str, ignore_case: bool) -> Option<&Value> {
+        let segments = split_path(path);
+        let (key, parents) = segments.split_last()?;
+        let mut document = self;
+        for parent in parents {
+            document = &document.get_block(parent, ignore_case)?.document;
+        }
+        document.get_pair(key, ignore_case).map(|kvp| &kvp.value)
+    }
+
+    /// Looks up a value by path, walking nested groups/objects separated by `/` or `.`, e.g.
+    /// `IMAGE/LINES`. Each level is matched by exact name.
+    pub fn get(&self, path: &str) -> Option<&Value> {
+        self.lookup(path, false)
+    }
+
+    /// Same as `get`, but names are compared ignoring ASCII case so `IMAGE` also matches
+    /// `Image` in hand-edited labels
+    pub fn get_ignore_case(&self, path: &str) -> Option<&Value> {
+        self.lookup(path, true)
+    }
+
+    /// Returns the number of key/value pairs in the tree, however deeply nested
+    pub fn len(&self) -> usize {
+        let mut count = 0;
+        self.for_each_pair(&mut vec![], &mut |_, _| count += 1);
+        count
+    }
+
+    /// Whether the tree holds no key/value pairs at all. Empty blocks don't count.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Counts the key/value pairs in the tree by the detected type of their value. Many
+    /// `Undetermined` values suggest the label uses syntax the parser doesn't understand.
+    /// # Example
+    /// ```
+    /// use pvl::{PvlDocument, ValueType};
+    ///
+    /// let doc = PvlDocument::parse("A = 1\nB = 2\nC = 0.5\nEND\n").unwrap();
+    /// assert_eq!(doc.type_histogram()[&ValueType::Integer], 2);
+    /// ```
+    pub fn type_histogram(&self) -> HashMap<ValueType, usize> {
+        let mut histogram = HashMap::new();
+        self.for_each_pair(&mut vec![], &mut |_, kvp| {
+            *histogram.entry(kvp.value.value_type()).or_insert(0) += 1;
+        });
+        histogram
+    }
+
+    /// Whether a value exists at the given path, as looked up by `get`
+    pub fn contains_key(&self, path: &str) -> bool {
+        self.get(path).is_some()
+    }
+
+    /// Returns the names of the keys and pointers at the top level in source order, each once.
+    /// Keys within blocks aren't included.
+    /// # Example
+    /// ```
+    /// use pvl::PvlDocument;
+    ///
+    /// let doc = PvlDocument::parse("B = 1\nA = 2\nGROUP = G\n  C = 3\nEND_GROUP = G\nB = 4\nEND\n").unwrap();
+    /// assert_eq!(doc.keys(), vec!["B", "A"]);
+    /// ```
+    pub fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = vec![];
+        for node in self.nodes.iter() {
+            if let PvlNode::Pair(kvp) = node {
+                if let Some(k) = key_name(kvp).filter(|k| !keys.iter().any(|e| e == k)) {
+                    keys.push(k.to_owned());
+                }
+            }
+        }
+        keys
+    }
+
+    /// Looks up every value at the given path, in source order. PDS labels may legitimately
+    /// repeat a key (e.g. multiple `NOTE`s), where `get` only returns the first.
+    pub fn get_all(&self, path: &str) -> Vec<&Value> {
+        let segments = split_path(path);
+        let Some((key, parents)) = segments.split_last() else {
+            return vec![];
+        };
+        let mut document = self;
+        for parent in parents {
+            match document.get_block(parent, false) {
+                Some(g) => document = &g.document,
+                None => return vec![],
+            }
+        }
+        document
+            .nodes
+            .iter()
+            .filter_map(|n| match n {
+                PvlNode::Pair(kvp) if key_name(kvp) == Some(key) => Some(&kvp.value),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the contents of the GROUP/OBJECT at the given name or path
+    pub fn get_group(&self, name: &str) -> Option<&PvlDocument> {
+        let segments = split_path(name);
+        if segments.is_empty() {
+            return None;
+        }
+        let mut document = self;
+        for segment in segments {
+            document = &document.get_block(segment, false)?.document;
+        }
+        Some(document)
+    }
+
+    /// Iterates over the top level pairs and blocks in source order
+    pub fn iter(&self) -> std::slice::Iter<'_, PvlNode> {
+        self.nodes.iter()
+    }
+
+    /// Visits every key/value pair in the tree in source order, however deeply nested, along
+    /// with its path: the names of the enclosing blocks followed by the key
+    /// # Example
+    /// ```
+    /// use pvl::PvlDocument;
+    ///
+    /// let doc = PvlDocument::parse("A = 1\nGROUP = IMAGE\n  LINES = 1024\nEND_GROUP = IMAGE\nEND\n").unwrap();
+    /// let mut paths = vec![];
+    /// doc.walk(|path, _| paths.push(path.join(".")));
+    /// assert_eq!(paths, vec!["A", "IMAGE.LINES"]);
+    /// ```
+    pub fn walk<F: FnMut(&[&str], &KeyValuePair)>(&self, mut f: F) {
+        self.for_each_pair(&mut vec![], &mut |path, kvp| f(path, kvp));
+    }
+
+    /// Iterates over every GROUP/OBJECT block in the document, however deeply nested, along with
+    /// its dotted path. Blocks come in source order, each before the blocks nested within it.
+    /// # Example
+    /// ```
+    /// use pvl::PvlDocument;
+    ///
+    /// let doc = PvlDocument::parse("OBJECT = IMAGE\n  GROUP = STATS\n  END_GROUP = STATS\nEND_OBJECT = IMAGE\nEND\n").unwrap();
+    /// let paths = doc.iter_groups().map(|(path, _)| path).collect::<Vec<String>>();
+    /// assert_eq!(paths, vec!["IMAGE", "IMAGE.STATS"]);
+    /// ```
+    pub fn iter_groups(&self) -> impl Iterator<Item = (String, &PvlGroup)> {
+        let mut blocks = vec![];
+        self.collect_blocks("", &mut blocks);
+        blocks.into_iter()
+    }
+
+    /// Returns the name given to the document's root, if any
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Names the document's root, or clears the name with `None`
+    pub fn set_name(&mut self, name: Option<&str>) {
+        self.name = name.map(|n| n.to_owned());
+    }
+
+    /// Returns the dotted path of every key in source order, starting with the root's name when
+    /// it has one, so keys from several documents can be told apart once combined
+    pub fn prefix_keys(&self) -> Vec<String> {
+        let mut keys = vec![];
+        self.for_each_pair(&mut vec![], &mut |path, _| {
+            keys.push(match &self.name {
+                Some(name) => format!("{}.{}", name, path.join(".")),
+                None => path.join("."),
+            })
+        });
+        keys
+    }
+
+    /// Returns every value whose dotted path matches the pattern, along with that path. A `*`
+    /// segment matches any single name, so `*.LINES` finds `LINES` in every top-level block.
+    /// # Example
+    /// ```
+    /// use pvl::PvlDocument;
+    ///
+    /// let doc = PvlDocument::parse("GROUP = IMAGE\n  LINES = 1024\nEND_GROUP = IMAGE\nEND\n").unwrap();
+    /// let matches = doc.paths_matching("IMAGE.*");
+    /// assert_eq!(matches[0].0, "IMAGE.LINES");
+    /// ```
+    pub fn paths_matching(&self, pattern: &str) -> Vec<(String, &Value)> {
+        let pattern = split_path(pattern);
+        let mut matches = vec![];
+        self.for_each_pair(&mut vec![], &mut |path, kvp| {
+            if path.len() == pattern.len()
+                && path
+                    .iter()
+                    .zip(pattern.iter())
+                    .all(|(s, p)| *p == "*" || s == p)
+            {
+                matches.push((path.join("."), &kvp.value));
+            }
+        });
+        matches
+    }
+
+    /// Returns the names of the blocks enclosing the given 1-based source line, outermost first.
+    /// A block's opening and closing lines count as inside it.
+    /// # Example
+    /// ```
+    /// use pvl::PvlDocument;
+    ///
+    /// let doc = PvlDocument::parse("GROUP = IMAGE\n  LINES = 1024\nEND_GROUP = IMAGE\nEND\n").unwrap();
+    /// assert_eq!(doc.scope_at_line(2), vec!["IMAGE"]);
+    /// assert!(doc.scope_at_line(4).is_empty());
+    /// ```
+    pub fn scope_at_line(&self, line: usize) -> Vec<String> {
+        let mut scope = vec![];
+        let mut document = self;
+        while let Some(g) = document.nodes.iter().find_map(|n| match n {
+            PvlNode::Block(g) if g.lines.0 <= line && line <= g.lines.1 && g.lines.0 > 0 => Some(g),
+            _ => None,
+        }) {
+            scope.push(g.name.clone());
+            document = &g.document;
+        }
+        scope
+    }
+
+    /// Overlays another document onto this one, replacing the values of keys present in both.
+    /// Same as `merge_with` in `MergeMode::Replace`.
+    /// # Example
+    /// ```
+    /// use pvl::PvlDocument;
+    ///
+    /// let mut base = PvlDocument::parse("LINES = 1024\nSAMPLES = 512\nEND\n").unwrap();
+    /// base.merge(&PvlDocument::parse("LINES = 2048\nEND\n").unwrap());
+    /// assert_eq!(base.get("LINES").unwrap().parse_u32().unwrap(), 2048);
+    /// ```
+    pub fn merge(&mut self, other: &PvlDocument) {
+        self.merge_with(other, MergeMode::Replace)
+    }
+
+    /// Overlays another document onto this one. Blocks of the same kind and name (the first,
+    /// when repeated) are merged recursively, so a GROUP never merges into an OBJECT. Other
+    /// blocks are added at the end. Keys only in `other` are added at the end, and keys present
+    /// in both are handled per `mode`.
+    pub fn merge_with(&mut self, other: &PvlDocument, mode: MergeMode) {
+        let mut merged_keys: Vec<&Symbol> = vec![];
+        for node in other.nodes.iter() {
+            match node {
+                PvlNode::Block(theirs) => {
+                    let ours = self.nodes.iter_mut().find_map(|n| match n {
+                        PvlNode::Block(g) if g.kind == theirs.kind && g.name == theirs.name => {
+                            Some(g)
+                        }
+                        _ => None,
+                    });
+                    match ours {
+                        Some(g) => g.document.merge_with(&theirs.document, mode),
+                        None => self.nodes.push(node.clone()),
+                    }
+                }
+                PvlNode::Pair(kvp) if mode == MergeMode::Replace => {
+                    // All of the other document's occurrences of the key go in at once
+                    if merged_keys.contains(&&kvp.key) {
+                        continue;
+                    }
+                    merged_keys.push(&kvp.key);
+                    let replacements = other
+                        .nodes
+                        .iter()
+                        .filter(|n| matches!(n, PvlNode::Pair(p) if p.key == kvp.key))
+                        .cloned()
+                        .collect::<Vec<PvlNode>>();
+                    let is_key = |n: &PvlNode| matches!(n, PvlNode::Pair(p) if p.key == kvp.key);
+                    match self.nodes.iter().position(is_key) {
+                        Some(first) => {
+                            self.nodes.retain(|n| !is_key(n));
+                            self.nodes.splice(first..first, replacements);
+                        }
+                        None => self.nodes.extend(replacements),
+                    }
+                }
+                PvlNode::Pair(_) => self.nodes.push(node.clone()),
+            }
+        }
+    }
+
+    /// Lists the changes from this document to another, comparing values by dotted key path.
+    /// Changed and removed keys come first in this document's order, then added keys in the
+    /// other's. A repeated key is compared occurrence by occurrence. Blocks without any keys
+    /// aren't reported, and neither are values differing only in whitespace, such as arrays
+    /// wrapped differently.
+    /// # Example
+    /// ```
+    /// use pvl::{Change, PvlDocument};
+    ///
+    /// let old = PvlDocument::parse("LINES = 1024\nEND\n").unwrap();
+    /// let new = PvlDocument::parse("LINES = 2048\nEND\n").unwrap();
+    /// assert!(matches!(&old.diff(&new)[0], Change::Modified { key_path, .. } if key_path == "LINES"));
+    /// ```
+    pub fn diff(&self, other: &PvlDocument) -> Vec<Change> {
+        let ours = self.numbered_pairs();
+        let theirs = other.numbered_pairs();
+        let (our_index, their_index) = (index_numbered(&ours), index_numbered(&theirs));
+        let mut changes = vec![];
+        for (path, n, old) in ours.iter() {
+            match their_index.get(&(path.as_str(), *n)) {
+                Some(new) if !values_match(new, old) => changes.push(Change::Modified {
+                    key_path: path.clone(),
+                    old: (*old).clone(),
+                    new: (*new).clone(),
+                }),
+                Some(_) => {}
+                None => changes.push(Change::Removed {
+                    key_path: path.clone(),
+                    value: (*old).clone(),
+                }),
+            }
+        }
+        for (path, n, new) in theirs.iter() {
+            if !our_index.contains_key(&(path.as_str(), *n)) {
+                changes.push(Change::Added {
+                    key_path: path.clone(),
+                    value: (*new).clone(),
+                });
+            }
+        }
+        changes
+    }
+
+    /// Lists the dotted path and value of every key in source order, along with how many times
+    /// the same path came before it
+    fn numbered_pairs(&self) -> Vec<(String, usize, &Value)> {
+        let mut pairs: Vec<(String, usize, &Value)> = vec![];
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        self.for_each_pair(&mut vec![], &mut |path, kvp| {
+            let path = path.join(".");
+            let count = seen.entry(path.clone()).or_default();
+            pairs.push((path, *count, &kvp.value));
+            *count += 1;
+        });
+        pairs
+    }
+
+    /// Returns the dotted path of each block name used by more than one GROUP/OBJECT within
+    /// the same parent, once each, in source order. Blocks of the same name in different
+    /// parents aren't duplicates.
+    /// # Example
+    /// ```
+    /// use pvl::PvlDocument;
+    ///
+    /// let doc = PvlDocument::parse("GROUP = BAND\nEND_GROUP = BAND\nGROUP = BAND\nEND_GROUP = BAND\nEND\n").unwrap();
+    /// assert_eq!(doc.duplicate_blocks(), vec!["BAND"]);
+    /// ```
+    pub fn duplicate_blocks(&self) -> Vec<String> {
+        let mut duplicates = vec![];
+        self.collect_duplicate_blocks("", &mut duplicates);
+        duplicates
+    }
+
+    /// Appends the paths of duplicated block names at this level, then within each block
+    fn collect_duplicate_blocks(&self, prefix: &str, duplicates: &mut Vec<String>) {
+        let mut seen: Vec<&str> = vec![];
+        for node in self.nodes.iter() {
+            let PvlNode::Block(g) = node else {
+                continue;
+            };
+            let path = if prefix.is_empty() {
+                g.name.clone()
+            } else {
+                format!("{}.{}", prefix, g.name)
+            };
+            if seen.contains(&g.name.as_str()) {
+                if !duplicates.contains(&path) {
+                    duplicates.push(path.clone());
+                }
+            } else {
+                seen.push(&g.name);
+            }
+            g.document.collect_duplicate_blocks(&path, duplicates);
+        }
+    }
+
+    /// Checks that every one of the required paths is present, returning the missing ones in
+    /// the order they were given
+    /// # Example
+    /// ```
+    /// use pvl::PvlDocument;
+    ///
+    /// let doc = PvlDocument::parse("PRODUCT_ID = N_001\nEND\n").unwrap();
+    /// let missing = doc.validate_required(&["PRODUCT_ID", "INSTRUMENT_ID"]).unwrap_err();
+    /// assert_eq!(missing, vec!["INSTRUMENT_ID"]);
+    /// ```
+    pub fn validate_required(&self, required: &[&str]) -> Result<(), Vec<String>> {
+        let missing = required
+            .iter()
+            .filter(|path| self.get(path).is_none())
+            .map(|path| path.to_string())
+            .collect::<Vec<String>>();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(missing)
+        }
+    }
+}
@@ -1,10 +1,38 @@
 use anyhow::Result;
 use regex::Regex;
-use std::{borrow::Cow, fs, path::Path};
+use std::{
+    borrow::Cow, fmt, fs, io::BufRead, ops::Range, path::Path, str::FromStr, time::Duration,
+};
 
 #[macro_use]
 extern crate lazy_static;
 
+mod datetime;
+pub use datetime::*;
+
+mod builder;
+pub use builder::PvlBuilder;
+
+mod document;
+pub use document::*;
+
+mod writer;
+pub use writer::WriterOptions;
+
+mod schema;
+pub use schema::{Schema, SchemaError};
+
+#[cfg(feature = "parallel")]
+mod parallel;
+
+#[cfg(feature = "serde")]
+mod ser;
+
+#[cfg(feature = "serde")]
+mod de;
+#[cfg(feature = "serde")]
+pub use de::from_document;
+
 /// Parse error types
 #[derive(Debug)]
 pub enum Error {
@@ -16,6 +44,8 @@ pub enum Error {
     ValueTypeParseError,
     InvalidEncoding(String),
     General(String),
+    Io(std::io::Error),
+    Overflow,
 }
 
 /// PVL Symbol types
@@ -54,8 +84,25 @@ pub enum ValueUnits {
     Seconds,
 }
 
+impl FromStr for ValueUnits {
+    type Err = Error;
+
+    /// Maps the text between a value's `<...>` brackets to a unit. Case insensitive.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_uppercase().as_str() {
+            "DEGC" | "CELCIUS" | "CELSIUS" => Ok(ValueUnits::Celcius),
+            "DEGF" | "FARENHEIT" | "FAHRENHEIT" => Ok(ValueUnits::Farenheit),
+            "DEG" | "DEGREE" | "DEGREES" | "°" => Ok(ValueUnits::Degrees),
+            "RAD" | "RADIAN" | "RADIANS" => Ok(ValueUnits::Radians),
+            "MS" | "MSEC" | "MILLISECOND" | "MILLISECONDS" => Ok(ValueUnits::Milliseconds),
+            "S" | "SEC" | "SECOND" | "SECONDS" => Ok(ValueUnits::Seconds),
+            _ => Err(Error::ValueTypeParseError),
+        }
+    }
+}
+
 /// PVL right-hand value data types
-#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
 pub enum ValueType {
     Undetermined,
     Array,
@@ -65,15 +112,187 @@ pub enum ValueType {
     Bool,
     Flag, // A string but not wrapped in quotes
     BitMask,
+    Radix, // base#digits# e.g. 16#FF#
+    DateTime,
+    Date,  // A date without a time, e.g. 2014-07-29 or 2014-210
+    Time,  // A time of day without a date, e.g. 14:32:05
+    Range, // min..max or min:max. Only detected with ReaderOptions::detect_ranges
 }
 
-/// Contains PVL right-hand values and flags
-#[derive(Debug, Clone)]
+impl ValueType {
+    /// Determines the data type of a raw value based on regex matches, as `Value::new` does
+    /// # Example
+    /// ```
+    /// use pvl::ValueType;
+    ///
+    /// assert_eq!(ValueType::classify("3.14"), ValueType::Float);
+    /// ```
+    pub fn classify(value_raw: &str) -> ValueType {
+        // Whitespace around the value, e.g. left over from joining continuation lines, never
+        // changes its type
+        let value_raw = value_raw.trim();
+        if BOOL_DETERMINATE.is_match(value_raw) {
+            ValueType::Bool
+        } else if STRING_DETERMINATE.is_match(value_raw) {
+            ValueType::String
+        } else if ARRAY_DETERMINATE.is_match(value_raw) {
+            ValueType::Array
+        } else if DATETIME_DETERMINATE.is_match(value_raw) {
+            ValueType::DateTime
+        } else if DATE_DETERMINATE.is_match(value_raw) {
+            ValueType::Date
+        } else if TIME_DETERMINATE.is_match(value_raw) {
+            ValueType::Time
+        } else if FLOAT_DETERMINATE.is_match(value_raw) {
+            ValueType::Float
+        } else if BITMASK_DETERMINATE.is_match(value_raw) {
+            ValueType::BitMask
+        } else if RADIX_DETERMINATE.is_match(value_raw) {
+            ValueType::Radix
+        } else if INTEGER_DETERMINATE.is_match(value_raw) {
+            ValueType::Integer
+        } else if FLAG_DETERMINATE.is_match(value_raw) {
+            ValueType::Flag
+        } else {
+            ValueType::Undetermined
+        }
+    }
+}
+
+impl FromStr for ValueType {
+    type Err = Error;
+
+    /// Maps a variant name such as `Float` or `Integer` to its type. Case insensitive.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_uppercase().as_str() {
+            "UNDETERMINED" => Ok(ValueType::Undetermined),
+            "ARRAY" => Ok(ValueType::Array),
+            "STRING" => Ok(ValueType::String),
+            "FLOAT" => Ok(ValueType::Float),
+            "INTEGER" => Ok(ValueType::Integer),
+            "BOOL" => Ok(ValueType::Bool),
+            "FLAG" => Ok(ValueType::Flag),
+            "BITMASK" => Ok(ValueType::BitMask),
+            "RADIX" => Ok(ValueType::Radix),
+            "DATETIME" => Ok(ValueType::DateTime),
+            "DATE" => Ok(ValueType::Date),
+            "TIME" => Ok(ValueType::Time),
+            "RANGE" => Ok(ValueType::Range),
+            _ => Err(Error::ValueTypeParseError),
+        }
+    }
+}
+
+/// Contains PVL right-hand values and flags. Values are equal when both their raw text and
+/// detected type match.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Value {
     value_raw: String,
     value_type: ValueType,
 }
 
+/// A value that holds `TRUE`/`FALSE` in some labels and a descriptive flag in others, as
+/// returned by `Value::try_as_bool_or_flag`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoolOrFlag {
+    Bool(bool),
+    Flag(String),
+}
+
+/// Checks whether every `"` opened in the text has been closed
+pub(crate) fn is_quote_closed(text: &str) -> bool {
+    text.matches('"').count().is_multiple_of(2)
+}
+
+/// Returns how many more `(`/`{` than `)`/`}` the text has, not counting those in quotes
+pub(crate) fn open_brackets(text: &str) -> i32 {
+    let mut in_quotes = false;
+    text.chars().fold(0, |depth, c| match c {
+        '"' => {
+            in_quotes = !in_quotes;
+            depth
+        }
+        '(' | '{' if !in_quotes => depth + 1,
+        ')' | '}' if !in_quotes => depth - 1,
+        _ => depth,
+    })
+}
+
+/// Splits the inside of a sequence or set on its top level commas. Commas within nested
+/// `(...)`/`{...}` or within a quoted string don't split. Elements are trimmed. Arrays nested
+/// more than `max_depth` levels deep within it are an error.
+fn split_elements(inner: &str, max_depth: usize) -> Result<Vec<&str>, Error> {
+    if inner.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    let mut elements = vec![];
+    let mut depth = 0_i32;
+    let mut in_quotes = false;
+    let mut start = 0;
+    for (i, c) in inner.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '(' | '{' if !in_quotes => {
+                depth += 1;
+                if depth as usize > max_depth {
+                    return Err(Error::Syntax("max nesting depth exceeded".to_owned()));
+                }
+            }
+            ')' | '}' if !in_quotes => depth -= 1,
+            ',' if !in_quotes && depth == 0 => {
+                elements.push(inner[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    elements.push(inner[start..].trim());
+    Ok(elements)
+}
+
+/// Returns the text of a key wrapped in double quotes, such as `"MY KEY"`, without the quotes
+fn unquote_key(symbol: &str) -> Option<&str> {
+    if symbol.len() >= 2 && symbol.starts_with('"') && symbol.ends_with('"') {
+        Some(&symbol[1..symbol.len() - 1])
+    } else {
+        None
+    }
+}
+
+/// Removes a `/* ... */` comment closing out the text, as in `IMAGE /* done */`, along with
+/// the whitespace before it. Comment markers inside a quoted string are left alone.
+pub(crate) fn strip_trailing_comment(text: &str) -> &str {
+    let trimmed = text.trim_end();
+    if !trimmed.ends_with("*/") {
+        return text;
+    }
+    let mut in_quotes = false;
+    for (i, c) in trimmed.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '/' if !in_quotes && trimmed[i..].starts_with("/*") => {
+                return trimmed[..i].trim_end();
+            }
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Returns the trimmed text of each `/* ... */` comment in the given text
+pub(crate) fn comments_within(text: &str) -> Vec<String> {
+    let mut comments = vec![];
+    let mut rest = text;
+    while let Some(open) = rest.find("/*") {
+        let Some(close) = rest[open + 2..].find("*/") else {
+            break;
+        };
+        comments.push(rest[open + 2..open + 2 + close].trim().to_owned());
+        rest = &rest[open + 2 + close + 2..];
+    }
+    comments
+}
+
 /// Formats an error object to a string via {:?} Debug derived method
 macro_rules! t {
     ($error_message:expr) => {
@@ -84,13 +303,28 @@ macro_rules! t {
 lazy_static! {
     static ref BOOL_DETERMINATE: Regex = Regex::new("^\"(TRUE|FALSE)\"$").unwrap();
     static ref STRING_DETERMINATE: Regex = Regex::new("^\".*\"$").unwrap();
-    static ref ARRAY_DETERMINATE: Regex = Regex::new("^\\(.*\\)$").unwrap();
-    static ref FLOAT_DETERMINATE: Regex = Regex::new("^-*[0-9]+\\.[0-9][ ]*").unwrap();
+    static ref ARRAY_DETERMINATE: Regex = Regex::new("^(\\(.*\\)|\\{.*\\})$").unwrap();
+    static ref FLOAT_DETERMINATE: Regex =
+        Regex::new("^-*[0-9]+(\\.[0-9]|\\.?[eE][+-]?[0-9])[ ]*").unwrap();
     static ref INTEGER_DETERMINATE: Regex = Regex::new("^[+-]*[0-9]+[^#a-zA-Z]*[ ]*").unwrap();
-    static ref FLAG_DETERMINATE: Regex = Regex::new("^[a-zA-Z_]+[a-zA-Z0-9]+$").unwrap();
-    static ref BITMASK_DETERMINATE: Regex = Regex::new("^[1-8]*#+[0-1]+#+$").unwrap();
+    static ref FLAG_DETERMINATE: Regex = Regex::new("^[a-zA-Z_][a-zA-Z0-9_]+$").unwrap();
+    static ref BITMASK_DETERMINATE: Regex = Regex::new("^([1-8]*)#+([0-1]+)#+$").unwrap();
+    static ref UNITS_DETERMINATE: Regex = Regex::new("^(.*?)\\s*<\\s*([^<>]+?)\\s*>$").unwrap();
+    static ref RANGE_DETERMINATE: Regex = Regex::new(
+        "^\\(?\\s*([+-]?[0-9]+(?:\\.[0-9]*)?(?:[eE][+-]?[0-9]+)?)\\s*(?:\\.\\.|:)\\s*([+-]?[0-9]+(?:\\.[0-9]*)?(?:[eE][+-]?[0-9]+)?)\\s*\\)?$"
+    )
+    .unwrap();
+    static ref RADIX_DETERMINATE: Regex = Regex::new("^([+-]?)([0-9]+)#([0-9A-Fa-f]+)#$").unwrap();
 }
-const LINE_CONTINUATION_PREFIX: &str = "                                     ";
+/// Default minimum indentation of a value continuation line
+pub const DEFAULT_CONTINUATION_INDENT: usize = 37;
+
+/// Default limit on how deeply blocks (or arrays) may be nested, guarding against labels that
+/// would otherwise exhaust the stack
+pub const DEFAULT_MAX_DEPTH: usize = 64;
+
+/// Tab stop width used when measuring the indentation of a line
+const TAB_WIDTH: usize = 8;
 
 // I think you'll get a lot of value out of this sorta thing for parsing libraries.
 /// Implements the miscellanous parsing functions for Value
@@ -104,7 +338,7 @@ macro_rules! impl_parse_fn {
             if self.value_type != ValueType::Undetermined && self.value_type != $value_type {
                 Err(Error::InvalidType)
             } else {
-                match self.value_raw.parse::<$type>() {
+                match self.strip_units().parse::<$type>() {
                     Ok(v) => Ok(v),
                     Err(_) => Err(Error::ValueTypeParseError),
                 }
@@ -113,9 +347,63 @@ macro_rules! impl_parse_fn {
     };
 }
 
+/// Implements a predicate for Value checking whether it was detected as the given type
+macro_rules! impl_is_fn {
+    ($fn_name:ident, $value_type:expr) => {
+        pub fn $fn_name(&self) -> bool {
+            self.value_type == $value_type
+        }
+    };
+}
+
+/// Implements a float parsing function for Value that rejects results too large to represent,
+/// along with a variant that can accept the non-finite spellings `inf`, `infinity` and `NaN`
+macro_rules! impl_parse_float_fn {
+    ($fn_name:ident, $with_fn_name:ident, $type:ty) => {
+        pub fn $fn_name(&self) -> Result<$type, Error> {
+            self.$with_fn_name(false)
+        }
+
+        #[doc = concat!(
+            "Same as `", stringify!($fn_name), "`, but a value spelled `inf`, `infinity` or ",
+            "`NaN` (any case, optionally signed) is returned as is when `allow_non_finite` is ",
+            "set. Otherwise it's an `Error::ValueTypeParseError`. Values out of range, such as ",
+            "`1e400`, are an `Error::Overflow` either way."
+        )]
+        pub fn $with_fn_name(&self, allow_non_finite: bool) -> Result<$type, Error> {
+            if self.value_type != ValueType::Undetermined && self.value_type != ValueType::Float {
+                Err(Error::InvalidType)
+            } else {
+                let text = self.strip_units();
+                match text.parse::<$type>() {
+                    Ok(v) if v.is_finite() => Ok(v),
+                    Ok(v) if is_non_finite_literal(text) => {
+                        if allow_non_finite {
+                            Ok(v)
+                        } else {
+                            Err(Error::ValueTypeParseError)
+                        }
+                    }
+                    // Out of range values like 1e400 parse "successfully" to infinity
+                    Ok(_) => Err(Error::Overflow),
+                    Err(_) => Err(Error::ValueTypeParseError),
+                }
+            }
+        }
+    };
+}
+
+/// Whether a number is spelled as infinity or NaN, as Rust's float parsing accepts
+fn is_non_finite_literal(text: &str) -> bool {
+    let unsigned = text.trim().trim_start_matches(['+', '-']);
+    ["inf", "infinity", "nan"]
+        .iter()
+        .any(|l| unsigned.eq_ignore_ascii_case(l))
+}
+
 impl Value {
-    impl_parse_fn!(parse_f32, f32, ValueType::Float);
-    impl_parse_fn!(parse_f64, f64, ValueType::Float);
+    impl_parse_float_fn!(parse_f32, parse_f32_with_non_finite, f32);
+    impl_parse_float_fn!(parse_f64, parse_f64_with_non_finite, f64);
     impl_parse_fn!(parse_u8, u8, ValueType::Integer);
     impl_parse_fn!(parse_u16, u16, ValueType::Integer);
     impl_parse_fn!(parse_u32, u32, ValueType::Integer);
@@ -128,56 +416,575 @@ impl Value {
     impl_parse_fn!(parse_bool, bool, ValueType::Bool);
     impl_parse_fn!(parse_flag, String, ValueType::Flag);
 
+    impl_is_fn!(is_integer, ValueType::Integer);
+    impl_is_fn!(is_float, ValueType::Float);
+    impl_is_fn!(is_string, ValueType::String);
+    impl_is_fn!(is_bool, ValueType::Bool);
+    impl_is_fn!(is_array, ValueType::Array);
+    impl_is_fn!(is_flag, ValueType::Flag);
+    impl_is_fn!(is_bitmask, ValueType::BitMask);
+
     /// Constructs a new Value object and determines type of provided raw data
     pub fn new(value_raw: &str) -> Self {
         Value {
             value_raw: value_raw.to_owned(),
-            value_type: Value::determine_type(value_raw),
+            value_type: ValueType::classify(value_raw),
         }
     }
 
-    /// Determines the data type of the raw value based on regex matches.
-    fn determine_type(value_raw: &str) -> ValueType {
-        if BOOL_DETERMINATE.is_match(value_raw) {
-            ValueType::Bool
-        } else if STRING_DETERMINATE.is_match(value_raw) {
-            ValueType::String
-        } else if ARRAY_DETERMINATE.is_match(value_raw) {
-            ValueType::Array
-        } else if FLOAT_DETERMINATE.is_match(value_raw) {
-            ValueType::Float
-        } else if BITMASK_DETERMINATE.is_match(value_raw) {
-            ValueType::BitMask
-        } else if INTEGER_DETERMINATE.is_match(value_raw) {
-            ValueType::Integer
-        } else if FLAG_DETERMINATE.is_match(value_raw) {
-            ValueType::Flag
+    /// Constructs a Value with the given type, bypassing type detection
+    /// # Example
+    /// ```
+    /// use pvl::{Value, ValueType};
+    ///
+    /// let v = Value::from_typed("0042", ValueType::String);
+    /// assert_eq!(v.value_type(), ValueType::String);
+    /// assert_eq!(v.as_str(), "0042");
+    /// ```
+    pub fn from_typed(value_raw: &str, value_type: ValueType) -> Self {
+        Value {
+            value_raw: value_raw.to_owned(),
+            value_type,
+        }
+    }
+
+    /// Constructs a Float value from a number, written in the shortest form that reads back as
+    /// the same number. The text always has a decimal point, with an upper case `E` for any
+    /// exponent. Infinity and NaN have no PVL form, so they're an `Error::ValueTypeParseError`.
+    /// # Example
+    /// ```
+    /// use pvl::{Value, ValueType};
+    ///
+    /// assert_eq!(Value::from_f64(5.0).unwrap().as_str(), "5.0");
+    /// assert_eq!(Value::from_f64(0.00001).unwrap().as_str(), "1.0E-5");
+    /// assert_eq!(Value::from_f64(5.0).unwrap().value_type(), ValueType::Float);
+    /// assert!(Value::from_f64(f64::NAN).is_err());
+    /// ```
+    pub fn from_f64(v: f64) -> Result<Self, Error> {
+        if !v.is_finite() {
+            return Err(Error::ValueTypeParseError);
+        }
+        let text = format!("{:?}", v);
+        let text = match text.split_once('e') {
+            Some((mantissa, exponent)) if mantissa.contains('.') => {
+                format!("{}E{}", mantissa, exponent)
+            }
+            Some((mantissa, exponent)) => format!("{}.0E{}", mantissa, exponent),
+            None => text,
+        };
+        Ok(Value::from_typed(&text, ValueType::Float))
+    }
+
+    /// Returns the raw text of the value, exactly as it appeared in the source
+    pub fn as_str(&self) -> &str {
+        &self.value_raw
+    }
+
+    /// Whether the value was written in double quotes, as strings are and booleans may be, so
+    /// output built from parsed values can keep `"BAR"` and `BAR` apart. A single-quoted symbol
+    /// string doesn't count; see `is_quoted` for either kind.
+    /// # Example
+    /// ```
+    /// use pvl::Value;
+    ///
+    /// assert!(Value::new("\"BAR\"").was_quoted());
+    /// assert!(!Value::new("BAR").was_quoted());
+    /// ```
+    pub fn was_quoted(&self) -> bool {
+        self.quote_char() == Some('"')
+    }
+
+    /// Returns the detected (or given) type of the value
+    pub fn value_type(&self) -> ValueType {
+        self.value_type
+    }
+
+    /// Returns the trimmed raw value without any trailing `<units>`
+    fn strip_units(&self) -> &str {
+        let raw = self.value_raw.trim();
+        match UNITS_DETERMINATE.captures(raw) {
+            Some(caps) => caps.get(1).map_or("", |m| m.as_str()),
+            None => raw,
+        }
+    }
+
+    /// Returns the value without any trailing `<units>`, trimmed, e.g. `12.5` for `12.5 <km/s>`
+    pub fn raw_without_unit(&self) -> &str {
+        self.strip_units()
+    }
+
+    /// Returns the literal text between the value's trailing `<...>`, whether or not it maps to
+    /// a `ValueUnits`. Same as `units`.
+    pub fn unit_raw(&self) -> Option<&str> {
+        self.units()
+    }
+
+    /// Returns the text of the value's trailing `<units>`, if present. Compound units such as
+    /// `<M/S**2>` are returned verbatim.
+    pub fn units(&self) -> Option<&str> {
+        UNITS_DETERMINATE
+            .captures(&self.value_raw)
+            .and_then(|caps| caps.get(2))
+            .map(|m| m.as_str())
+    }
+
+    /// Returns the value's units if they map to a known `ValueUnits`. Compound units don't.
+    pub fn unit_enum(&self) -> Option<ValueUnits> {
+        self.units().and_then(|u| u.parse().ok())
+    }
+
+    /// Reads a time interval such as `1500 <ms>` or `2.5 <s>` as a Duration. A value without
+    /// time units is an `Error::InvalidType`, and a negative one an `Error::ValueTypeParseError`.
+    /// # Example
+    /// ```
+    /// use pvl::Value;
+    /// use std::time::Duration;
+    ///
+    /// assert_eq!(Value::new("1500 <ms>").as_duration().unwrap(), Duration::from_millis(1500));
+    /// ```
+    pub fn as_duration(&self) -> Result<Duration, Error> {
+        let seconds = match self.unit_enum() {
+            Some(ValueUnits::Seconds) => self.parse_number()?,
+            Some(ValueUnits::Milliseconds) => self.parse_number()? / 1000.0,
+            _ => return Err(Error::InvalidType),
+        };
+        Duration::try_from_secs_f64(seconds).map_err(|_| Error::ValueTypeParseError)
+    }
+
+    /// Returns the character the raw value was wrapped in: `"` for text strings, `'` for
+    /// symbol strings, or `None` if it wasn't quoted
+    pub fn quote_char(&self) -> Option<char> {
+        let raw = self.value_raw.trim();
+        ['"', '\'']
+            .into_iter()
+            .find(|q| raw.len() >= 2 && raw.starts_with(*q) && raw.ends_with(*q))
+    }
+
+    /// Reads a value that is either a boolean or a flag. `TRUE` and `FALSE` are booleans whether
+    /// quoted or bare (in any case), any other flag is returned as-is.
+    /// # Example
+    /// ```
+    /// use pvl::{BoolOrFlag, Value};
+    ///
+    /// assert_eq!(Value::new("TRUE").try_as_bool_or_flag().unwrap(), BoolOrFlag::Bool(true));
+    /// assert_eq!(
+    ///     Value::new("PARTIAL").try_as_bool_or_flag().unwrap(),
+    ///     BoolOrFlag::Flag("PARTIAL".to_owned())
+    /// );
+    /// ```
+    pub fn try_as_bool_or_flag(&self) -> Result<BoolOrFlag, Error> {
+        let text = match self.value_type {
+            ValueType::Bool => self.value_raw.trim().trim_matches('"'),
+            ValueType::Flag | ValueType::Undetermined => self.value_raw.trim(),
+            _ => return Err(Error::InvalidType),
+        };
+        if text.eq_ignore_ascii_case("TRUE") {
+            Ok(BoolOrFlag::Bool(true))
+        } else if text.eq_ignore_ascii_case("FALSE") {
+            Ok(BoolOrFlag::Bool(false))
+        } else if text.is_empty() {
+            Err(Error::ValueTypeParseError)
         } else {
-            ValueType::Undetermined
+            Ok(BoolOrFlag::Flag(text.to_owned()))
         }
     }
 
+    /// Whether the raw value was wrapped in quotes
+    pub fn is_quoted(&self) -> bool {
+        self.quote_char().is_some()
+    }
+
+    /// Parses a string value, returning its content without the surrounding double quotes.
+    /// A doubled `""` within the string is an escaped quote and is returned as a single `"`.
     pub fn parse_string(&self) -> Result<String, Error> {
         // I'm gonna allow parsing if the type is undetermined. A type being undetermined is my problem, but
         // the user will have the option (and risk) of parsing it
         if self.value_type != ValueType::Undetermined && self.value_type != ValueType::String {
             Err(Error::InvalidType)
         } else {
-            Ok(self.value_raw.replace("\"", "").to_owned())
+            let raw = self.value_raw.trim();
+            let content = match self.quote_char() {
+                Some('"') => &raw[1..raw.len() - 1],
+                _ => raw,
+            };
+            Ok(content.replace("\"\"", "\""))
+        }
+    }
+
+    /// Parses a based integer of the form `base#digits#` (e.g. `16#FF#`, `2#0101#`), optionally
+    /// preceded by a sign as in `-16#FF#`. Hex digits are accepted in either case.
+    pub fn parse_radix(&self) -> Result<i64, Error> {
+        if self.value_type != ValueType::Undetermined
+            && self.value_type != ValueType::Radix
+            && self.value_type != ValueType::BitMask
+        {
+            Err(Error::InvalidType)
+        } else {
+            match RADIX_DETERMINATE.captures(&self.value_raw) {
+                Some(caps) => match caps[2].parse::<u32>() {
+                    Ok(radix) if (2..=16).contains(&radix) => {
+                        // The sign is parsed along with the digits so i64::MIN is reachable
+                        i64::from_str_radix(&format!("{}{}", &caps[1], &caps[3]), radix)
+                            .map_err(|_| Error::ValueTypeParseError)
+                    }
+                    _ => Err(Error::ValueTypeParseError),
+                },
+                None => Err(Error::ValueTypeParseError),
+            }
         }
     }
 
-    /// Parses the raw data value to an array of Values. Throws an error if we are not an array type
+    /// Whether the value is written in one of the range forms `min..max` or `min:max`,
+    /// optionally wrapped in parentheses
+    pub(crate) fn is_range_syntax(&self) -> bool {
+        RANGE_DETERMINATE.is_match(self.strip_units().trim())
+    }
+
+    /// Parses the endpoints of a range. Recognizes `min..max` and `min:max`, either optionally
+    /// wrapped in parentheses, and a two element numeric sequence `(min, max)`. Any type is
+    /// accepted, since without `ReaderOptions::detect_ranges` a range such as `10:20` is typed
+    /// as a time.
+    /// # Example
+    /// ```
+    /// use pvl::Value;
+    ///
+    /// assert_eq!(Value::new("10:20").parse_range().unwrap(), (10.0, 20.0));
+    /// assert_eq!(Value::new("(-1.5..2)").parse_range().unwrap(), (-1.5, 2.0));
+    /// ```
+    pub fn parse_range(&self) -> Result<(f64, f64), Error> {
+        let Some(caps) = RANGE_DETERMINATE.captures(self.strip_units().trim()) else {
+            return match self.parse_f64_array()?.as_slice() {
+                [min, max] => Ok((*min, *max)),
+                _ => Err(Error::ValueTypeParseError),
+            };
+        };
+        let endpoint = |i: usize| -> Result<f64, Error> {
+            match caps[i].parse::<f64>() {
+                Ok(v) if v.is_finite() => Ok(v),
+                Ok(_) => Err(Error::Overflow),
+                Err(_) => Err(Error::ValueTypeParseError),
+            }
+        };
+        Ok((endpoint(1)?, endpoint(2)?))
+    }
+
+    /// Parses a bit mask of the form `width#bits#`, e.g. `8#00001111#`, where the width prefix
+    /// is optional. Having more bits than the declared width (or than fit in a u64) is an
+    /// `Error::Overflow`.
+    /// # Example
+    /// ```
+    /// use pvl::Value;
+    ///
+    /// assert_eq!(Value::new("8#00001111#").parse_bitmask().unwrap(), 15);
+    /// ```
+    pub fn parse_bitmask(&self) -> Result<u64, Error> {
+        if self.value_type != ValueType::Undetermined && self.value_type != ValueType::BitMask {
+            return Err(Error::InvalidType);
+        }
+        let caps = BITMASK_DETERMINATE
+            .captures(self.value_raw.trim())
+            .ok_or(Error::ValueTypeParseError)?;
+        let bits = &caps[2];
+        let width = match &caps[1] {
+            "" => 64,
+            w => w.parse::<usize>().map_err(|_| Error::ValueTypeParseError)?,
+        };
+        if bits.len() > width.min(64) {
+            return Err(Error::Overflow);
+        }
+        u64::from_str_radix(bits, 2).map_err(|_| Error::ValueTypeParseError)
+    }
+
+    /// Returns the value as PVL text, exactly as it appeared in the source
+    pub fn to_pvl(&self) -> String {
+        self.value_raw.to_owned()
+    }
+
+    /// Parses the raw data value to an array of Values. Throws an error if we are not an array type.
+    /// Both sequences `(...)` and sets `{...}` are arrays, and may be nested within each other.
+    /// Arrays nested more than `DEFAULT_MAX_DEPTH` levels deep are an error.
     pub fn parse_array(&self) -> Result<Vec<Value>, Error> {
-        if self.value_type != ValueType::Array {
+        self.parse_array_with_max_depth(DEFAULT_MAX_DEPTH)
+    }
+
+    /// Same as `parse_array`, with a custom limit on how deeply arrays may be nested. The array
+    /// itself is the first level. Text that isn't wrapped in `()` or `{}`, as a value built with
+    /// `from_typed` may be, is an `Error::InvalidType`.
+    pub fn parse_array_with_max_depth(&self, max_depth: usize) -> Result<Vec<Value>, Error> {
+        let raw = self.value_raw.trim();
+        let bracketed = (raw.starts_with('(') && raw.ends_with(')'))
+            || (raw.starts_with('{') && raw.ends_with('}'));
+        if self.value_type != ValueType::Array || raw.len() < 2 || !bracketed {
             Err(Error::InvalidType)
+        } else if max_depth == 0 {
+            Err(Error::Syntax("max nesting depth exceeded".to_owned()))
         } else {
-            Ok(self.value_raw[1..(self.value_raw.len() - 1)]
-                .split(',')
+            Ok(split_elements(&raw[1..(raw.len() - 1)], max_depth - 1)?
+                .into_iter()
                 .map(Value::new)
                 .collect())
         }
     }
+
+    /// Parses a numeric scalar (integer or float) to an f64
+    fn parse_number(&self) -> Result<f64, Error> {
+        match self.value_type {
+            ValueType::Float | ValueType::Integer | ValueType::Undetermined => self
+                .strip_units()
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| Error::ValueTypeParseError)
+                .and_then(|v| {
+                    if v.is_finite() {
+                        Ok(v)
+                    } else if is_non_finite_literal(self.strip_units()) {
+                        Err(Error::ValueTypeParseError)
+                    } else {
+                        Err(Error::Overflow)
+                    }
+                }),
+            _ => Err(Error::InvalidType),
+        }
+    }
+
+    /// Parses an array of exactly `N` numeric elements, e.g. an RGB triplet or a vector
+    /// # Example
+    /// ```
+    /// use pvl::Value;
+    ///
+    /// let rgb: [f64; 3] = Value::new("(255, 128, 0)").as_array_fixed().unwrap();
+    /// assert_eq!(rgb, [255.0, 128.0, 0.0]);
+    /// ```
+    pub fn as_array_fixed<const N: usize>(&self) -> Result<[f64; N], Error> {
+        let elements = self.parse_array()?;
+        if elements.len() != N {
+            return Err(Error::General(format!(
+                "Expected an array of {} elements, found {}",
+                N,
+                elements.len()
+            )));
+        }
+        let mut values = [0.0; N];
+        for (v, e) in values.iter_mut().zip(elements.iter()) {
+            *v = e.parse_number()?;
+        }
+        Ok(values)
+    }
+
+    /// Leniently reads a boolean, accepting `TRUE` or `FALSE` in any case with or without
+    /// surrounding quotes
+    pub fn as_bool(&self) -> Result<bool, Error> {
+        let raw = self.value_raw.trim();
+        let unquoted = match self.quote_char() {
+            Some(_) => &raw[1..raw.len() - 1],
+            None => raw,
+        };
+        if unquoted.eq_ignore_ascii_case("TRUE") {
+            Ok(true)
+        } else if unquoted.eq_ignore_ascii_case("FALSE") {
+            Ok(false)
+        } else {
+            Err(Error::ValueTypeParseError)
+        }
+    }
+
+    /// Reads every element of an array such as `(TRUE, FALSE, TRUE)` as a boolean
+    /// # Example
+    /// ```
+    /// use pvl::Value;
+    ///
+    /// let flags = Value::new("(TRUE, \"FALSE\")").as_bool_array().unwrap();
+    /// assert_eq!(flags, vec![true, false]);
+    /// ```
+    pub fn as_bool_array(&self) -> Result<Vec<bool>, Error> {
+        self.parse_array()?.iter().map(|e| e.as_bool()).collect()
+    }
+
+    /// Parses every element of a numeric array such as `(1.0, 2.5, 3)` to an f64. Integer
+    /// elements are accepted; any other element is an error.
+    /// # Example
+    /// ```
+    /// use pvl::Value;
+    ///
+    /// let v = Value::new("(1.0, 2.5, 3)").parse_f64_array().unwrap();
+    /// assert_eq!(v, vec![1.0, 2.5, 3.0]);
+    /// ```
+    pub fn parse_f64_array(&self) -> Result<Vec<f64>, Error> {
+        self.parse_array()?
+            .iter()
+            .map(|e| e.parse_number())
+            .collect()
+    }
+
+    /// Same as `parse_f64_array`, but the PDS null sentinels `N/A`, `UNK` and `NULL` (quoted or
+    /// not) are read as `f64::NAN` so arrays with gaps keep their length
+    /// # Example
+    /// ```
+    /// use pvl::Value;
+    ///
+    /// let v = Value::new("(1.0, N/A, 3.0)").as_f64_array_nan().unwrap();
+    /// assert!(v[1].is_nan());
+    /// ```
+    pub fn as_f64_array_nan(&self) -> Result<Vec<f64>, Error> {
+        self.parse_array()?
+            .iter()
+            .map(|e| {
+                let raw = e.value_raw.trim();
+                let unquoted = match e.quote_char() {
+                    Some(_) => &raw[1..raw.len() - 1],
+                    None => raw,
+                };
+                match unquoted {
+                    "N/A" | "UNK" | "NULL" => Ok(f64::NAN),
+                    _ => e.parse_number(),
+                }
+            })
+            .collect()
+    }
+
+    /// Collects every numeric scalar of a possibly nested array in order, descending into
+    /// nested sequences and sets. Any non-numeric leaf is an error.
+    /// # Example
+    /// ```
+    /// use pvl::Value;
+    ///
+    /// let v = Value::new("((1, 2), (3, 4))").flatten_numeric().unwrap();
+    /// assert_eq!(v, vec![1.0, 2.0, 3.0, 4.0]);
+    /// ```
+    pub fn flatten_numeric(&self) -> Result<Vec<f64>, Error> {
+        let mut values = vec![];
+        for e in self.parse_array()?.iter() {
+            match e.value_type {
+                ValueType::Array => values.extend(e.flatten_numeric()?),
+                _ => values.push(e.parse_number()?),
+            }
+        }
+        Ok(values)
+    }
+
+    /// Parses every element of an integer array such as `(1, 2, 3)` to an i64
+    pub fn parse_i64_array(&self) -> Result<Vec<i64>, Error> {
+        self.parse_array()?.iter().map(|e| e.parse_i64()).collect()
+    }
+
+    /// Parses every element of an array of strings. Both quoted strings and unquoted symbols
+    /// such as `(RED, "NEAR INFRARED")` are accepted; any other element is an error.
+    pub fn parse_string_array(&self) -> Result<Vec<String>, Error> {
+        self.parse_array()?
+            .iter()
+            .map(|e| match e.value_type {
+                ValueType::Flag => e.parse_flag(),
+                _ => e.parse_string(),
+            })
+            .collect()
+    }
+
+    /// Parses the record or byte offset of a pointer, e.g. `5` or `12345 <BYTES>`
+    fn parse_pointer_offset(&self) -> Result<(u64, Option<String>), Error> {
+        let offset = self
+            .strip_units()
+            .trim()
+            .parse::<u64>()
+            .map_err(|_| Error::ValueTypeParseError)?;
+        Ok((offset, self.units().map(|u| u.to_owned())))
+    }
+
+    /// Parses the target of a `^POINTER` value. Accepts `("FILE.IMG", 5)`, `12345 <BYTES>`
+    /// and `"FILE.IMG"`.
+    /// # Example
+    /// ```
+    /// use pvl::Value;
+    ///
+    /// let ptr = Value::new("(\"NRB_0001.IMG\", 5)").parse_pointer().unwrap();
+    /// assert_eq!(ptr.filename.as_deref(), Some("NRB_0001.IMG"));
+    /// assert_eq!(ptr.offset, Some(5));
+    /// ```
+    pub fn parse_pointer(&self) -> Result<Pointer, Error> {
+        match self.value_type {
+            ValueType::String => Ok(Pointer {
+                filename: Some(self.parse_string()?),
+                offset: None,
+                units: None,
+            }),
+            ValueType::Array => match self.parse_array()?.as_slice() {
+                [file, offset] if file.value_type == ValueType::String => {
+                    let (offset, units) = offset.parse_pointer_offset()?;
+                    Ok(Pointer {
+                        filename: Some(file.parse_string()?),
+                        offset: Some(offset),
+                        units,
+                    })
+                }
+                [file] if file.value_type == ValueType::String => Ok(Pointer {
+                    filename: Some(file.parse_string()?),
+                    offset: None,
+                    units: None,
+                }),
+                _ => Err(Error::ValueTypeParseError),
+            },
+            _ => {
+                let (offset, units) = self.parse_pointer_offset()?;
+                Ok(Pointer {
+                    filename: None,
+                    offset: Some(offset),
+                    units,
+                })
+            }
+        }
+    }
+}
+
+/// Writes the canonical text form of the value: scalars as written, strings re-quoted with
+/// embedded quotes doubled, and arrays re-wrapped with their elements separated by `, `
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let raw = self.value_raw.trim();
+        match self.value_type {
+            ValueType::Array => match self.parse_array() {
+                Ok(elements) => {
+                    let (open, close) = (&raw[..1], &raw[raw.len() - 1..]);
+                    write!(f, "{}", open)?;
+                    for (i, e) in elements.iter().enumerate() {
+                        if i > 0 {
+                            write!(f, ", ")?;
+                        }
+                        write!(f, "{}", e)?;
+                    }
+                    write!(f, "{}", close)
+                }
+                Err(_) => write!(f, "{}", raw),
+            },
+            ValueType::String => match self.parse_string() {
+                Ok(s) => write!(f, "\"{}\"", s.replace('"', "\"\"")),
+                Err(_) => write!(f, "{}", raw),
+            },
+            _ => write!(f, "{}", raw),
+        }
+    }
+}
+
+impl FromStr for Value {
+    type Err = Error;
+
+    /// Strict alternative to `Value::new`: text whose type can't be determined is an
+    /// `Error::ValueTypeParseError` rather than an `Undetermined` value
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = Value::new(s);
+        if value.value_type == ValueType::Undetermined {
+            Err(Error::ValueTypeParseError)
+        } else {
+            Ok(value)
+        }
+    }
+}
+
+/// The target of a `^POINTER` value: a detached file, a record or byte offset, or both
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pointer {
+    pub filename: Option<String>,
+    /// Record number (1-based) or, if `units` is `BYTES`, byte offset
+    pub offset: Option<u64>,
+    pub units: Option<String>,
 }
 
 /// Represents the basic KEY = VALUE pair in a PVL file
@@ -185,8 +992,57 @@ impl Value {
 pub struct KeyValuePair {
     pub key: Symbol,
     pub value: Value,
+    /// Text of the `/* ... */` comments between the previous statement and this one
+    pub(crate) comments: Vec<String>,
+    /// Text of the `/* ... */` comment at the end of the pair's value line, if any
+    pub(crate) trailing_comment: Option<String>,
+    /// Byte range of the statement in the source. Empty for pairs that weren't read from text.
+    pub(crate) span: Range<usize>,
 }
 
+impl KeyValuePair {
+    pub fn new(key: Symbol, value: Value) -> Self {
+        KeyValuePair {
+            key,
+            value,
+            comments: vec![],
+            trailing_comment: None,
+            span: 0..0,
+        }
+    }
+
+    /// Returns the byte range of the statement in the text it was read from, from the start of
+    /// its line (including any indentation) through the end of its value, which may span
+    /// several lines. `&content[kvp.span()]` is the statement's source. Offsets count the text as
+    /// the reader holds it, so they are shifted for content with CRLF line endings, which the
+    /// reader normalizes to LF. Empty if the pair wasn't read from text.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// Returns the text of the comments immediately preceding this pair in the source, without
+    /// their `/*`/`*/` markers. Only captured by `PvlReader::parse_document` when
+    /// `ReaderOptions::capture_comments` is set.
+    pub fn comments(&self) -> &[String] {
+        &self.comments
+    }
+
+    /// Returns the text of the comment after the value, as in `LINES = 1024 /* full frame */`,
+    /// without its markers. The comment isn't part of the value.
+    pub fn trailing_comment(&self) -> Option<&str> {
+        self.trailing_comment.as_deref()
+    }
+}
+
+/// Pairs are equal when their keys and values match. Comments and spans are ignored.
+impl PartialEq for KeyValuePair {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key && self.value == other.value
+    }
+}
+
+impl Eq for KeyValuePair {}
+
 /// Defines the shared properties of both GROUP and OBJECT
 pub trait PropertyGrouping {
     fn name(&self) -> String;
@@ -202,12 +1058,10 @@ macro_rules! get_property {
             Some(
                 self.properties
                     .iter()
-                    .filter(|p| match &p.key {
+                    .find(|p| match &p.key {
                         Symbol::Key(n) | Symbol::Pointer(n) => n == name,
                         _ => false,
-                    })
-                    .next()
-                    .unwrap()
+                    })?
                     .to_owned(),
             )
         }
@@ -230,82 +1084,421 @@ macro_rules! has_property {
     };
 }
 
-/// Represents the PVL GROUP...END_GROUP structure
-#[derive(Debug)]
-pub struct Group {
-    pub name: String,
-    pub properties: Vec<KeyValuePair>,
-}
+/// Represents the PVL GROUP...END_GROUP structure
+#[derive(Debug)]
+pub struct Group {
+    pub name: String,
+    pub properties: Vec<KeyValuePair>,
+}
+
+impl PropertyGrouping for Group {
+    fn name(&self) -> String {
+        self.name.to_owned()
+    }
+
+    fn properties(&self) -> Vec<KeyValuePair> {
+        self.properties.clone()
+    }
+
+    fn type_of(&self) -> Symbol {
+        Symbol::Group
+    }
+
+    get_property! {}
+    has_property! {}
+}
+
+/// Represents the PVL OBJECT...END_OBJECT structure
+#[derive(Debug)]
+pub struct Object {
+    pub name: String,
+    pub properties: Vec<KeyValuePair>,
+}
+
+impl PropertyGrouping for Object {
+    fn name(&self) -> String {
+        self.name.to_owned()
+    }
+
+    fn properties(&self) -> Vec<KeyValuePair> {
+        self.properties.clone()
+    }
+
+    fn type_of(&self) -> Symbol {
+        Symbol::Object
+    }
+
+    get_property! {}
+    has_property! {}
+}
+
+/// Options checked by `PvlReader` while parsing a document
+#[derive(Debug, Clone)]
+pub struct ReaderOptions {
+    /// Fail the parse with `Error::Syntax` on any value whose type can't be determined
+    pub error_on_undetermined: bool,
+    /// Hash the label text while parsing a document, available afterwards from
+    /// `PvlReader::content_hash`
+    pub compute_hash: bool,
+    /// How deeply GROUP/OBJECT blocks may be nested before the parse fails with
+    /// `Error::Syntax`
+    pub max_depth: usize,
+    /// Keep the comments preceding each key/value pair, available from
+    /// `KeyValuePair::comments`
+    pub capture_comments: bool,
+    /// Type values written as `min..max` or `min:max` (optionally in parentheses) as
+    /// `ValueType::Range`. Off by default, as `10:20` is otherwise a time of day.
+    pub detect_ranges: bool,
+    /// Treat a key line without an `=` as malformed rather than as a key with an empty value.
+    /// `read_key_value_pair_raw` fails with `Error::Syntax`, or in lenient mode skips the line
+    /// and returns a `Symbol::BlankLine` pair.
+    pub require_assignment: bool,
+}
+
+impl Default for ReaderOptions {
+    fn default() -> Self {
+        ReaderOptions {
+            error_on_undetermined: false,
+            compute_hash: false,
+            max_depth: DEFAULT_MAX_DEPTH,
+            capture_comments: false,
+            detect_ranges: false,
+            require_assignment: false,
+        }
+    }
+}
+
+/// 64-bit FNV-1a hash of the given bytes. Stable across runs and platforms.
+pub(crate) fn fnv1a_64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Main PVL parsing engine. Borrows the content it reads unless line endings need filtering.
+#[derive(Debug)]
+pub struct PvlReader<'a> {
+    content: Cow<'a, str>,
+    pos: usize,
+    continuation_indent: usize,
+    lenient: bool,
+    options: ReaderOptions,
+    content_hash: Option<u64>,
+    /// When set, recoverable errors while parsing a document are collected here instead of
+    /// failing the parse
+    collected_errors: Option<Vec<Error>>,
+    /// Pairs of matching offsets into `content` and the original bytes, from each place where
+    /// the two stop lining up (a dropped byte order mark, a CRLF
+    /// normalized to LF, or a Latin-1 byte transcoded to two UTF-8 bytes)
+    source_map: Vec<(usize, usize)>,
+    /// The character between a key and its value, `=` unless changed for another dialect
+    assignment: char,
+}
+
+impl<'a> PvlReader<'a> {
+    /// Constructs a new PVLReader object. Normalizes CRLF and CR line endings to LF. Expects UTF-8 encoded String
+    pub fn new(content: &'a str) -> Self {
+        PvlReader::with_content(
+            PvlReader::filter_linefeeds(content),
+            PvlReader::source_map(content.as_bytes(), 0, false),
+        )
+    }
+
+    /// Constructs a PVLReader from raw bytes, such as a detached label read straight from disk
+    /// or the network. A leading UTF-8 byte order mark is dropped. Text that isn't valid UTF-8 is
+    /// an `Error::InvalidEncoding`, unless the `latin1` feature is enabled, in which case it is
+    /// transcoded from Latin-1.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, Error> {
+        let (bytes, bom_len) = match bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+            Some(rest) => (rest, 3),
+            None => (bytes, 0),
+        };
+        match std::str::from_utf8(bytes) {
+            Ok(s) => Ok(PvlReader::with_content(
+                PvlReader::filter_linefeeds(s),
+                PvlReader::source_map(bytes, bom_len, false),
+            )),
+            #[cfg(feature = "latin1")]
+            Err(_) => {
+                let transcoded = bytes.iter().map(|b| *b as char).collect::<String>();
+                Ok(PvlReader::with_content(
+                    Cow::Owned(PvlReader::filter_linefeeds(&transcoded).into_owned()),
+                    PvlReader::source_map(bytes, bom_len, true),
+                ))
+            }
+            #[cfg(not(feature = "latin1"))]
+            Err(why) => Err(Error::InvalidEncoding(format!(
+                "Content is not valid UTF-8 after byte {}",
+                why.valid_up_to()
+            ))),
+        }
+    }
+
+    fn with_content(content: Cow<'a, str>, source_map: Vec<(usize, usize)>) -> Self {
+        PvlReader {
+            content,
+            pos: 0,
+            continuation_indent: DEFAULT_CONTINUATION_INDENT,
+            lenient: false,
+            options: ReaderOptions::default(),
+            content_hash: None,
+            collected_errors: None,
+            source_map,
+            assignment: '=',
+        }
+    }
+
+    /// Constructs a PvlReader from a stream, such as a file or socket, reading it a line at a
+    /// time only up to and including the `END` line. Anything after it, like the binary data of
+    /// an attached label, is left unread in the stream. The text is decoded as in `from_bytes`.
+    /// # Example
+    /// ```
+    /// use pvl::PvlReader;
+    /// use std::io::Cursor;
+    ///
+    /// let mut stream = Cursor::new(b"LINES = 1024\nEND\n\x00\x01".to_vec());
+    /// let doc = PvlReader::from_reader(&mut stream).unwrap().parse_document().unwrap();
+    /// assert_eq!(doc.get("LINES").unwrap().parse_u32().unwrap(), 1024);
+    /// assert_eq!(stream.position(), 17);
+    /// ```
+    pub fn from_reader<R: BufRead>(mut stream: R) -> Result<PvlReader<'static>, Error> {
+        let mut bytes = vec![];
+        let mut in_quotes = false;
+        let mut in_comment = false;
+        loop {
+            let line_start = bytes.len();
+            if stream.read_until(b'\n', &mut bytes).map_err(Error::Io)? == 0 {
+                break;
+            }
+            let line = String::from_utf8_lossy(&bytes[line_start..]);
+            if !in_quotes && !in_comment && strip_trailing_comment(&line).trim() == "END" {
+                break;
+            }
+            // An END inside a quoted string or a block comment doesn't end the label
+            if in_comment {
+                in_comment = !line.contains("*/");
+            } else {
+                in_quotes ^= line.matches('"').count() % 2 == 1;
+                if !in_quotes {
+                    in_comment = line
+                        .rfind("/*")
+                        .is_some_and(|open| !line[open..].contains("*/"));
+                }
+            }
+        }
+        Ok(PvlReader::from_bytes(&bytes)?.into_owned())
+    }
+
+    /// Takes ownership of the content, so the reader no longer borrows the text it was made from
+    fn into_owned(self) -> PvlReader<'static> {
+        PvlReader {
+            content: Cow::Owned(self.content.into_owned()),
+            pos: self.pos,
+            continuation_indent: self.continuation_indent,
+            lenient: self.lenient,
+            options: self.options,
+            content_hash: self.content_hash,
+            collected_errors: self.collected_errors,
+            source_map: self.source_map,
+            assignment: self.assignment,
+        }
+    }
+
+    /// Moves the caret back to the start of the content so the same reader can be used for
+    /// multiple passes without re-allocating the content. The content hash and any errors
+    /// collected by the previous pass are cleared.
+    pub fn reset(&mut self) {
+        self.pos = 0;
+        self.content_hash = None;
+        if let Some(errors) = self.collected_errors.as_mut() {
+            errors.clear();
+        }
+    }
+
+    /// Returns the caret position, to be passed to `restore` after reading ahead
+    pub fn checkpoint(&self) -> usize {
+        self.pos
+    }
+
+    /// Moves the caret back (or forward) to a position from `checkpoint`. A position past the
+    /// end of the content or within a multibyte character is an `Error::Programming`.
+    pub fn restore(&mut self, pos: usize) -> Result<(), Error> {
+        if !self.content.is_char_boundary(pos) {
+            return Err(Error::Programming(format!(
+                "Cannot restore to position {} of content {} bytes long",
+                pos,
+                self.content.len()
+            )));
+        }
+        self.pos = pos;
+        Ok(())
+    }
+
+    /// Sets the minimum indentation (in columns, tabs advancing to the next multiple of 8) at
+    /// which a line is treated as the continuation of the previous value
+    pub fn set_continuation_indent(&mut self, indent: usize) {
+        self.continuation_indent = indent;
+    }
+
+    /// Returns the minimum indentation of a value continuation line
+    pub fn continuation_indent(&self) -> usize {
+        self.continuation_indent
+    }
+
+    /// Sets the character separating keys from values, for dialects that write `KEY : VALUE`.
+    /// It must be an ASCII character that has no other meaning in PVL, so not whitespace, a
+    /// quote, a bracket, a comma, a unit's angle bracket or the `/` of a comment. Otherwise an
+    /// `Error::Programming` is returned.
+    pub fn set_assignment_char(&mut self, c: char) -> Result<(), Error> {
+        if !c.is_ascii() || c.is_ascii_whitespace() || "\"'/(){},<>".contains(c) {
+            return Err(Error::Programming(format!(
+                "{:?} can't be used as the assignment character",
+                c
+            )));
+        }
+        self.assignment = c;
+        Ok(())
+    }
+
+    /// Returns the character separating keys from values, `=` by default
+    pub fn assignment_char(&self) -> char {
+        self.assignment
+    }
+
+    /// Enables recovery from malformed labels. In lenient mode a line starting with `=`, where
+    /// a key is expected, is read as a continuation of the previous value instead of a pair
+    /// with an empty key, and a GROUP/OBJECT without a name is given one such as
+    /// `UNNAMED_GROUP_3` (after its line) rather than failing `parse_document`.
+    pub fn set_lenient(&mut self, lenient: bool) {
+        self.lenient = lenient;
+    }
+
+    /// Whether malformed lines are recovered from rather than read as written
+    pub fn is_lenient(&self) -> bool {
+        self.lenient
+    }
+
+    /// Sets the options checked while parsing a document
+    pub fn set_options(&mut self, options: ReaderOptions) {
+        self.options = options;
+    }
 
-impl PropertyGrouping for Group {
-    fn name(&self) -> String {
-        self.name.to_owned()
+    /// Returns the options checked while parsing a document
+    pub fn options(&self) -> &ReaderOptions {
+        &self.options
     }
 
-    fn properties(&self) -> Vec<KeyValuePair> {
-        self.properties.clone()
+    /// Returns the 64-bit FNV-1a hash of the label text read by the last `parse_document`, from
+    /// where parsing started through the `END` statement. `None` unless
+    /// `ReaderOptions::compute_hash` was set.
+    pub fn content_hash(&self) -> Option<u64> {
+        self.content_hash
     }
 
-    fn type_of(&self) -> Symbol {
-        Symbol::Group
+    /// Returns the 1-based line number of the given byte position
+    pub(crate) fn line_of(&self, pos: usize) -> usize {
+        let end = pos.min(self.content.len());
+        self.content.as_bytes()[..end]
+            .iter()
+            .filter(|b| **b == b'\n')
+            .count()
+            + 1
     }
 
-    get_property! {}
-    has_property! {}
-}
+    /// Returns the byte offset the statement before the caret ends at, ignoring the line break
+    /// and any whitespace between them
+    pub(crate) fn statement_end(&self) -> usize {
+        let end = self.pos.min(self.content.len());
+        self.content[..end].trim_end().len()
+    }
 
-/// Represents the PVL OBJECT...END_OBJECT structure
-#[derive(Debug)]
-pub struct Object {
-    pub name: String,
-    pub properties: Vec<KeyValuePair>,
-}
+    /// Returns the 1-based line number of the caret
+    pub fn line_number(&self) -> usize {
+        self.line_of(self.pos)
+    }
 
-impl PropertyGrouping for Object {
-    fn name(&self) -> String {
-        self.name.to_owned()
+    /// Returns the number of characters (Unicode scalar values) in the content
+    pub fn char_len(&self) -> usize {
+        self.content.chars().count()
     }
 
-    fn properties(&self) -> Vec<KeyValuePair> {
-        self.properties.clone()
+    /// Returns the size of the content in bytes. Caret positions are byte offsets in this range.
+    pub fn byte_len(&self) -> usize {
+        self.content.len()
     }
 
-    fn type_of(&self) -> Symbol {
-        Symbol::Object
+    /// Returns the size of the content in bytes, same as `byte_len`
+    pub fn len(&self) -> usize {
+        self.content.len()
     }
 
-    get_property! {}
-    has_property! {}
-}
+    /// Whether there is no content at all, regardless of the caret
+    pub fn is_empty(&self) -> bool {
+        self.content.is_empty()
+    }
 
-/// Main PVL parsing engine
-#[derive(Debug)]
-pub struct PvlReader {
-    content: String,
-    pos: usize,
-}
+    /// Returns the content from the caret to the end, which is empty once everything is read
+    pub fn remaining(&self) -> &str {
+        self.content.get(self.pos..).unwrap_or_default()
+    }
 
-impl PvlReader {
-    /// Constructs a new PVLReader object. Filters CRLF to LF. Expects UTF-8 encoded String
-    pub fn new(content: &str) -> Self {
-        PvlReader {
-            content: PvlReader::filter_linefeeds(content),
-            pos: 0,
+    /// Normalizes CRLF and lone CR line endings to LF, so every line ends in a single `\n`.
+    /// Only copies the text if there is something to normalize.
+    fn filter_linefeeds(content: &'a str) -> Cow<'a, str> {
+        if content.contains('\r') {
+            Cow::Owned(content.replace("\r\n", "\n").replace('\r', "\n"))
+        } else {
+            Cow::Borrowed(content)
+        }
+    }
+
+    /// Builds the `source_map` of content decoded from the given bytes, which followed a byte
+    /// order mark of `bom_len` bytes and were transcoded from Latin-1 if `latin1` is set
+    fn source_map(bytes: &[u8], bom_len: usize, latin1: bool) -> Vec<(usize, usize)> {
+        // Left empty when nothing needs mapping, so plain UTF-8 text doesn't allocate
+        let mut map = if bom_len > 0 {
+            vec![(0, bom_len)]
+        } else {
+            vec![]
+        };
+        let mut content_pos = 0;
+        for (i, b) in bytes.iter().enumerate() {
+            if *b == b'\r' && bytes.get(i + 1) == Some(&b'\n') {
+                // Dropped by normalizing the line ending
+                map.push((content_pos, bom_len + i + 1));
+            } else if latin1 && *b >= 0x80 {
+                content_pos += 2;
+                map.push((content_pos, bom_len + i + 1));
+            } else {
+                content_pos += 1;
+            }
         }
+        map
     }
 
-    /// Filters out `\r` from the text
-    fn filter_linefeeds(content: &str) -> String {
-        content.chars().filter(|f| *f != '\r').collect()
+    /// Maps a byte offset into the content to the matching offset in the original bytes, which
+    /// may have had a byte order mark, CRLF line endings or Latin-1 text
+    pub(crate) fn source_offset(&self, pos: usize) -> usize {
+        let (content_pos, source_pos) = match self.source_map.partition_point(|(c, _)| *c <= pos) {
+            0 => (0, 0),
+            n => self.source_map[n - 1],
+        };
+        source_pos + (pos - content_pos)
     }
 
-    /// Returns the character at the specified index, or `Error::Eof` if the  index is beyond the limit of the text
+    /// Returns the character at the specified byte index, or `Error::Eof` if the  index is beyond the limit of the text.
+    /// A multibyte character is decoded in full at its first byte. Its remaining bytes come back
+    /// as-is, which is fine for the ASCII structure of a label (`=`, newlines, quotes, etc).
     pub fn char_at(&self, indx: usize) -> Result<char, Error> {
         if indx >= self.content.len() {
             Err(Error::Eof)
         } else {
-            //Ok(self.content.chars().nth(indx).unwrap()) // Slow but correct(er)
-            Ok(self.content.as_bytes()[indx] as char) // WAY faster, but won't work for non 8-bit text files
+            let b = self.content.as_bytes()[indx];
+            if b.is_ascii() || !self.content.is_char_boundary(indx) {
+                Ok(b as char) // WAY faster than walking chars()
+            } else {
+                self.content[indx..].chars().next().ok_or(Error::Eof)
+            }
         }
     }
 
@@ -341,7 +1534,9 @@ impl PvlReader {
         self.pos + n < self.content.len()
     }
 
-    pub fn jump(&mut self, num_chars: usize) -> Result<(), Error> {
+    /// Moves the caret forward by the given number of bytes. Returns how far it actually moved,
+    /// which is less than requested if the end of the content was reached first.
+    pub fn jump(&mut self, num_chars: usize) -> Result<usize, Error> {
         if self.is_eof() {
             Err(Error::Eof)
         } else {
@@ -352,17 +1547,17 @@ impl PvlReader {
                 num_chars
             };
             self.pos += do_num_chars;
-            Ok(())
+            Ok(do_num_chars)
         }
     }
 
     pub fn is_at_line_start(&self) -> Result<bool, Error> {
-        if self.pos > 0 && self.pos - 1 > self.content.len() {
+        if self.pos > self.content.len() {
             Err(Error::Eof)
         } else if self.pos == 0 {
             Ok(true)
         } else {
-            let c = self.char_at(self.pos - 1).unwrap();
+            let c = self.char_at(self.pos - 1)?;
             match c {
                 '\r' | '\n' => Ok(true),
                 _ => Ok(false),
@@ -374,8 +1569,8 @@ impl PvlReader {
         if self.is_eof() || self.pos + 1 >= self.content.len() {
             Ok(false)
         } else {
-            let c = self.current_char().unwrap();
-            let n = self.peek_char().unwrap();
+            let c = self.current_char()?;
+            let n = self.peek_char()?;
             Ok(c == '/' && n == '*')
         }
     }
@@ -384,22 +1579,39 @@ impl PvlReader {
         if self.pos + 1 >= self.content.len() {
             Ok(false)
         } else {
-            let c = self.current_char().unwrap();
-            let n = self.peek_char().unwrap();
+            let c = self.current_char()?;
+            let n = self.peek_char()?;
             Ok(c == '*' && n == '/')
         }
     }
 
     pub fn skip_multiline_comment(&mut self) -> Result<String, Error> {
-        if !self.is_at_multiline_comment_start().unwrap() {
+        if !self.is_at_multiline_comment_start()? {
             Err(Error::CommentIsntComment)
         } else {
-            let mut comment_text = "".to_string();
-            while !self.is_at_multiline_comment_end().unwrap() {
-                comment_text.push(self.next_char().unwrap());
+            let start = self.pos + 2;
+            self.jump(2)?;
+            while !self.is_at_multiline_comment_end()? {
+                if !self.has_n_remaining(1) {
+                    return Err(Error::Syntax("Unterminated multiline comment".to_owned()));
+                }
+                self.next_char()?;
+            }
+            let comment_text = self.content[start..self.pos].to_string();
+            if self.jump(2)? < 2 {
+                return Err(Error::Syntax("Unterminated multiline comment".to_owned()));
             }
-            self.jump(2).unwrap();
-            Ok(comment_text[1..(comment_text.len() - 2)].to_string())
+            Ok(comment_text)
+        }
+    }
+
+    /// Checks whether the line starting at the caret holds nothing but a `/* ... */` comment
+    pub fn is_at_comment_line(&self) -> Result<bool, Error> {
+        if !self.is_at_line_start()? {
+            Ok(false)
+        } else {
+            let line = self.content.as_bytes()[self.pos..self.line_end()].trim_ascii();
+            Ok(line.starts_with(b"/*") && line.ends_with(b"*/"))
         }
     }
 
@@ -413,21 +1625,12 @@ impl PvlReader {
     pub fn is_at_group(&self) -> Result<bool, Error> {
         if !self.has_n_remaining(5) {
             Ok(false)
-        } else if !self.is_at_line_start().unwrap() {
+        } else if !self.is_at_line_start()? {
             Err(Error::Programming(t!(
                 "Attempt to check if at group when not at start of line"
             )))
         } else {
-            Ok(vec![
-                self.char_at_pos_plus_n(0).unwrap(),
-                self.char_at_pos_plus_n(1).unwrap(),
-                self.char_at_pos_plus_n(2).unwrap(),
-                self.char_at_pos_plus_n(3).unwrap(),
-                self.char_at_pos_plus_n(4).unwrap(),
-            ]
-            .into_iter()
-            .collect::<String>()
-                == "GROUP")
+            Ok(self.content.as_bytes()[self.pos..].starts_with(b"GROUP"))
         }
     }
 
@@ -435,58 +1638,45 @@ impl PvlReader {
         if !self.has_n_remaining(6) {
             Ok(false)
         } else {
-            Ok(vec![
-                self.char_at_pos_plus_n(0).unwrap(),
-                self.char_at_pos_plus_n(1).unwrap(),
-                self.char_at_pos_plus_n(2).unwrap(),
-                self.char_at_pos_plus_n(3).unwrap(),
-                self.char_at_pos_plus_n(4).unwrap(),
-                self.char_at_pos_plus_n(5).unwrap(),
-            ]
-            .into_iter()
-            .collect::<String>()
-                == "OBJECT")
+            Ok(self.content.as_bytes()[self.pos..].starts_with(b"OBJECT"))
         }
     }
 
     pub fn is_at_end(&self) -> bool {
-        if self.has_n_remaining(3) {
-            let mut s = String::new();
-
-            s.push(self.char_at_pos_plus_n(0).unwrap());
-            s.push(self.char_at_pos_plus_n(1).unwrap());
-            s.push(self.char_at_pos_plus_n(2).unwrap());
-
-            s == "END"
-        } else {
-            false
-        }
+        self.has_n_remaining(3) && self.content.as_bytes()[self.pos..].starts_with(b"END")
     }
 
     pub fn read_symbol(&mut self) -> Result<Symbol, Error> {
-        if self.is_at_value_line_continuation().unwrap() {
+        if self.is_at_value_line_continuation()? {
             Err(Error::Syntax(
                 "Value line continuation without a preceeding key value pair".to_owned(),
             ))
-        } else if !self.is_at_line_start().unwrap() {
+        } else if !self.is_at_line_start()? {
             Err(Error::Programming(
                 "Attempt to read a key value pair when not at beginning of a line".to_owned(),
             ))
         } else {
-            let mut symbol_text = String::new();
+            let start = self.pos;
+            let mut in_quotes = false;
             while !self.is_eof() {
-                let c = self.current_char().unwrap();
-                if c != '\n' && c != '\r' && c != '=' {
-                    symbol_text.push(c);
-                } else {
+                let c = self.current_char()?;
+                if c == '"' {
+                    in_quotes = !in_quotes;
+                }
+                // An '=' inside a quoted key is part of the key
+                if c == '\n' || c == '\r' || (c == self.assignment && !in_quotes) {
                     break;
                 }
-                self.next_char().unwrap();
+                self.jump(1)?;
             }
 
-            symbol_text = symbol_text.trim().to_owned();
+            // Sliced rather than collected char by char so multibyte characters come through intact
+            let symbol_text =
+                strip_trailing_comment(self.slice(start, self.pos)?.trim()).to_owned();
             // println!("{} -> {}", symbol_text.len(), symbol_text);
-            if symbol_text.is_empty() {
+            if let Some(key) = unquote_key(&symbol_text) {
+                Ok(Symbol::Key(key.to_owned()))
+            } else if symbol_text.is_empty() {
                 Ok(Symbol::BlankLine)
             } else if symbol_text.starts_with('^') {
                 Ok(Symbol::Pointer(symbol_text))
@@ -507,24 +1697,91 @@ impl PvlReader {
     }
 
     pub fn read_remaining_line(&mut self) -> Result<String, Error> {
-        let mut line_text = String::new();
-        while !self.is_eof() {
-            if self.current_char().unwrap() == '=' {
-                self.jump(2).unwrap();
-            }
-            let c = self.current_char().unwrap();
-            if c != '\n' && c != '\r' {
-                line_text.push(c);
-            } else {
-                break;
-            }
-            if !self.is_eof() {
-                self.next_char()?;
-            }
+        // Step over the assignment operator left behind by read_symbol. Any whitespace around
+        // it is trimmed below.
+        if !self.is_eof() && self.current_char()? == self.assignment {
+            self.jump(1)?;
         }
+        self.read_line_text()
+    }
+
+    /// Reads the trimmed text from the caret to the end of the line, leaving the caret on the
+    /// line terminator
+    fn read_line_text(&mut self) -> Result<String, Error> {
+        // Taken as bytes so multibyte UTF-8 characters (e.g. the degree sign) survive
+        let start = self.pos.min(self.content.len());
+        let end = self.line_end();
+        self.pos = end;
+        Ok(
+            String::from_utf8_lossy(&self.content.as_bytes()[start..end])
+                .trim()
+                .to_owned(),
+        )
+    }
+
+    /// Returns the byte position of the end of the current line (the `\n` or end of content)
+    fn line_end(&self) -> usize {
+        match self.content.as_bytes()[self.pos.min(self.content.len())..]
+            .iter()
+            .position(|b| *b == b'\n')
+        {
+            Some(n) => self.pos + n,
+            None => self.content.len(),
+        }
+    }
+
+    /// Returns a slice of the content, or an error if the bounds split a multibyte character
+    fn slice(&self, start: usize, end: usize) -> Result<&str, Error> {
+        match self.content.get(start..end) {
+            Some(s) => Ok(s),
+            None => Err(Error::InvalidEncoding(format!(
+                "Range {}..{} is not on a character boundary",
+                start, end
+            ))),
+        }
+    }
+
+    /// Zero-copy variant of `read_symbol`. Returns the trimmed key text up to the `=` (or end
+    /// of line) as a slice of the content, leaving the caret on the `=`.
+    pub fn read_symbol_slice(&mut self) -> Result<&str, Error> {
+        if !self.is_at_line_start()? {
+            return Err(Error::Programming(
+                "Attempt to read a symbol when not at beginning of a line".to_owned(),
+            ));
+        }
+        let start = self.pos;
+        let assignment = self.assignment as u8;
+        let mut in_quotes = false;
+        let end = match self.content.as_bytes()[start..self.line_end()]
+            .iter()
+            .position(|b| {
+                if *b == b'"' {
+                    in_quotes = !in_quotes;
+                }
+                *b == assignment && !in_quotes
+            }) {
+            Some(n) => start + n,
+            None => self.line_end(),
+        };
+        self.pos = end;
+        let symbol = self.slice(start, end)?.trim();
+        Ok(unquote_key(symbol).unwrap_or(symbol))
+    }
 
-        line_text = line_text.trim().to_owned();
-        Ok(line_text)
+    /// Zero-copy variant of `read_remaining_line`. Returns the trimmed text from the caret (after
+    /// a leading `=`, if present) to the end of the line as a slice of the content, leaving the
+    /// caret on the line terminator.
+    pub fn read_remaining_line_slice(&mut self) -> Result<&str, Error> {
+        if self.is_eof() {
+            return Err(Error::Eof);
+        }
+        let mut start = self.pos;
+        if self.current_char()? == self.assignment {
+            start += 1;
+        }
+        let end = self.line_end();
+        self.pos = end;
+        Ok(self.slice(start, end)?.trim())
     }
 
     pub fn is_blank_line(&self) -> Result<bool, Error> {
@@ -537,10 +1794,9 @@ impl PvlReader {
         } else {
             let mut found_non_ws = false;
             for i in 0..100 {
-                if self.pos + i >= self.content.len() || self.char_at_pos_plus_n(i).unwrap() == '\n'
-                {
+                if self.pos + i >= self.content.len() || self.char_at_pos_plus_n(i)? == '\n' {
                     break;
-                } else if self.char_at_pos_plus_n(i).unwrap() != ' ' {
+                } else if self.char_at_pos_plus_n(i)? != ' ' {
                     found_non_ws = true;
                 }
             }
@@ -550,29 +1806,181 @@ impl PvlReader {
 
     pub fn is_at_equals(&self) -> Result<bool, Error> {
         match self.current_char() {
-            Ok(c) => Ok(c == '='),
+            Ok(c) => Ok(c == self.assignment),
             Err(why) => Err(why),
         }
     }
 
+    /// Checks whether the line at the caret continues the previous value: it is indented by at
+    /// least the continuation indent and holds text that is neither an assignment nor a comment
     pub fn is_at_value_line_continuation(&self) -> Result<bool, Error> {
-        if !self.is_at_line_start().unwrap() {
-            Ok(false)
-        } else if self.pos + LINE_CONTINUATION_PREFIX.len() >= self.content.len() {
-            Err(Error::Eof)
-        } else {
-            Ok(
-                &self.content[self.pos..(self.pos + LINE_CONTINUATION_PREFIX.len())]
-                    == LINE_CONTINUATION_PREFIX,
-            )
+        if !self.is_at_line_start()? {
+            return Ok(false);
+        }
+        let line = &self.content.as_bytes()[self.pos..self.line_end()];
+        let mut width = 0;
+        for (i, b) in line.iter().enumerate() {
+            match b {
+                b' ' => width += 1,
+                b'\t' => width += TAB_WIDTH - width % TAB_WIDTH,
+                _ => {
+                    let text = &line[i..];
+                    return Ok(width >= self.continuation_indent
+                        && !text.contains(&(self.assignment as u8))
+                        && !text.starts_with(b"/*"));
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    /// Returns true if the caret is at the start of a line whose first non-whitespace character
+    /// is `=`, i.e. a value with its key missing
+    fn is_at_stray_assignment(&self) -> Result<bool, Error> {
+        if !self.is_at_line_start()? {
+            return Ok(false);
         }
+        let line = &self.content.as_bytes()[self.pos..self.line_end()];
+        Ok(line
+            .iter()
+            .find(|b| !b.is_ascii_whitespace())
+            .is_some_and(|b| *b == self.assignment as u8))
+    }
+
+    /// Finds a value written on a line of its own after a `KEY =` with nothing following it, as
+    /// some generators lay out long values. Blank lines from the given line start on are
+    /// skipped. Returns the byte range of the value's line, or `None` if the next non-blank line
+    /// is a statement, comment or block keyword of its own.
+    pub(crate) fn value_line_after(&self, from: usize) -> Option<Range<usize>> {
+        let mut start = from;
+        while start < self.content.len() {
+            let end = self.content[start..]
+                .find('\n')
+                .map_or(self.content.len(), |n| start + n);
+            let line = self.content[start..end].trim();
+            if line.is_empty() {
+                start = end + 1;
+                continue;
+            }
+            let mut in_quotes = false;
+            let assignment = line.chars().any(|c| {
+                if c == '"' {
+                    in_quotes = !in_quotes;
+                }
+                c == self.assignment && !in_quotes
+            });
+            let keyword = matches!(
+                strip_trailing_comment(line).trim(),
+                "END" | "END_GROUP" | "END_OBJECT" | "GROUP" | "OBJECT"
+            );
+            return if assignment || line.starts_with("/*") || keyword {
+                None
+            } else {
+                Some(start..end)
+            };
+        }
+        None
     }
 
+    /// Moves the caret to the start of the next line that isn't empty. If the caret is already
+    /// at the start of a line, as it is after `read_key_value_pair_raw`, only empty lines are
+    /// skipped, so the first character of the following statement is never stepped over.
     pub fn jump_to_next_line(&mut self) -> Result<(), Error> {
-        while self.pos <= self.content.len() {
-            if self.char_at(self.pos).unwrap() == '\n' {
-                self.next_char()?;
+        if !self.is_eof() && !self.is_at_line_start()? {
+            self.skip_line()?;
+        }
+        while !self.is_eof() && self.current_char()? == '\n' {
+            self.pos += 1;
+        }
+        Ok(())
+    }
+
+    /// Reads the next KEY = VALUE statement, skipping blank lines and comments. Returns `None`
+    /// once `END` or the end of the content is reached.
+    pub fn read_next_pair(&mut self) -> Result<Option<KeyValuePair>, Error> {
+        if !self.skip_to_statement()? {
+            return Ok(None);
+        }
+        let kvp = self.read_key_value_pair_raw()?;
+        if kvp.key == Symbol::End {
+            Ok(None)
+        } else {
+            Ok(Some(kvp))
+        }
+    }
+
+    /// Advances the caret past blank lines and comments to the start of the next line holding
+    /// a statement. Returns false if the end of the content is reached first.
+    pub fn skip_to_statement(&mut self) -> Result<bool, Error> {
+        loop {
+            if self.is_eof() {
+                return Ok(false);
+            } else if !self.is_at_line_start()? {
+                self.skip_line()?;
+                continue;
+            }
+
+            let line_start = self.pos;
+            while !self.is_eof() && matches!(self.current_char()?, ' ' | '\t') {
+                self.jump(1)?;
+            }
+
+            if self.is_eof() || self.current_char()? == '\n' {
+                self.skip_line()?;
+            } else if self.is_at_multiline_comment_start()? {
+                self.skip_multiline_comment()?;
+                self.skip_line()?;
             } else {
+                self.pos = line_start;
+                return Ok(true);
+            }
+        }
+    }
+
+    /// Returns an iterator over the KEY = VALUE statements from the current caret position
+    /// through `END`. GROUP/OBJECT openers and terminators are yielded as statements too.
+    /// # Example
+    /// ```
+    /// use pvl::PvlReader;
+    ///
+    /// let mut reader = PvlReader::new("LINES = 1024\nSAMPLES = 1024\nEND\n");
+    /// for kvp in reader.pairs() {
+    ///     println!("{:?}", kvp.unwrap());
+    /// }
+    /// ```
+    pub fn pairs(&mut self) -> Pairs<'_, 'a> {
+        Pairs {
+            reader: self,
+            done: false,
+        }
+    }
+
+    /// Consumes the reader, returning an iterator that owns it and yields the same statements
+    /// as `pairs`. Useful for handing the iteration off to another thread or stream adapter.
+    /// # Example
+    /// ```
+    /// use pvl::PvlReader;
+    ///
+    /// let reader = PvlReader::new("LINES = 1024\nSAMPLES = 1024\nEND\n");
+    /// let keys = reader
+    ///     .into_pairs()
+    ///     .map(|kvp| kvp.unwrap().key)
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(keys.len(), 2);
+    /// ```
+    pub fn into_pairs(self) -> IntoPairs<'a> {
+        IntoPairs {
+            reader: self,
+            done: false,
+        }
+    }
+
+    /// Advances the caret past the newline that terminates the current line
+    pub fn skip_line(&mut self) -> Result<(), Error> {
+        while !self.is_eof() {
+            let c = self.current_char()?;
+            self.pos += 1;
+            if c == '\n' {
                 break;
             }
         }
@@ -586,33 +1994,146 @@ impl PvlReader {
         Ok(())
     }
 
+    /// Reads a KEY = VALUE statement along with any continuation lines. The caret is left at
+    /// the start of the line following the statement.
     pub fn read_key_value_pair_raw(&mut self) -> Result<KeyValuePair, Error> {
-        if self.is_at_value_line_continuation().unwrap() {
+        if self.is_at_value_line_continuation()? {
             Err(Error::Syntax(
                 "Value line continuation without a preceeding key value pair".to_owned(),
             ))
-        } else if !self.is_at_line_start().unwrap() {
+        } else if !self.is_at_line_start()? {
             Err(Error::Programming(
                 "Attempt to read a key value pair when not at beginning of a line".to_owned(),
             ))
         } else {
+            let start = self.pos;
             let mut value_string = String::new();
-            let key_res = self.read_symbol().unwrap();
-            value_string += self.read_remaining_line().unwrap().as_ref();
-
-            self.next_char()?;
-            while let Ok(b) = self.is_at_value_line_continuation() {
-                if b {
-                    value_string += self.read_remaining_line().unwrap().to_string().as_ref();
-                    self.next_char()?;
+            let key_res = self.read_symbol()?;
+            let assigned = !self.is_eof() && self.current_char()? == self.assignment;
+            if assigned && key_res == Symbol::BlankLine && !self.lenient {
+                return Err(Error::Syntax(format!(
+                    "empty key before '{}'",
+                    self.assignment
+                )));
+            }
+            if !assigned
+                && self.options.require_assignment
+                && matches!(key_res, Symbol::Key(_) | Symbol::Pointer(_))
+            {
+                if !self.lenient {
+                    return Err(Error::Syntax(format!(
+                        "expected '{}' in assignment",
+                        self.assignment
+                    )));
+                }
+                if !self.is_eof() {
+                    self.jump(1)?;
+                }
+                let mut kvp = KeyValuePair::new(Symbol::BlankLine, Value::new(""));
+                kvp.span = start..self.statement_end().max(start);
+                return Ok(kvp);
+            }
+            value_string += self.read_remaining_line()?.as_ref();
+
+            // Step over the line terminator, if the file doesn't end on this line
+            if !self.is_eof() {
+                self.jump(1)?;
+            }
+
+            // The value may start on a later line, e.g. `KEY =` followed by a blank line
+            if value_string.is_empty()
+                && assigned
+                && matches!(key_res, Symbol::Key(_) | Symbol::Pointer(_))
+            {
+                if let Some(line) = self.value_line_after(self.pos) {
+                    value_string = self.content[line.clone()].trim().to_owned();
+                    self.pos = line.end;
+                    if !self.is_eof() {
+                        self.jump(1)?;
+                    }
+                }
+            }
+
+            // A quoted string runs until its closing quote, whatever the indentation of the
+            // lines in between. Line breaks within it are normalized to a single space.
+            if !is_quote_closed(&value_string) {
+                while !self.is_eof() && !is_quote_closed(&value_string) {
+                    let line = self.read_line_text()?;
+                    if !line.is_empty() {
+                        value_string.push(' ');
+                        value_string += &line;
+                    }
+                    if !self.is_eof() {
+                        self.jump(1)?;
+                    }
+                }
+                if !is_quote_closed(&value_string) {
+                    return Err(Error::Syntax(format!(
+                        "Unterminated quoted string for {:?}",
+                        key_res
+                    )));
+                }
+            }
+
+            // Nothing after END belongs to the label (attached labels are followed by binary data)
+            while key_res != Symbol::End && !self.is_eof() {
+                if self.is_at_value_line_continuation()? {
+                    value_string += self.read_remaining_line()?.as_ref();
+                    if !self.is_eof() {
+                        self.jump(1)?;
+                    }
+                } else if self.lenient && self.is_at_stray_assignment()? {
+                    // Most likely a continuation line indented too little, so the text after
+                    // the `=` is joined onto the value
+                    while self.current_char()? != self.assignment {
+                        self.pos += 1;
+                    }
+                    value_string.push(' ');
+                    value_string += self.read_remaining_line()?.as_ref();
+                    if !self.is_eof() {
+                        self.jump(1)?;
+                    }
+                } else if self.is_at_comment_line()? {
+                    // A comment-only line between fragments doesn't end the value, as long as
+                    // the value continues on the line after it
+                    let comment_line = self.pos;
+                    self.skip_line()?;
+                    if !matches!(self.is_at_value_line_continuation(), Ok(true)) {
+                        self.pos = comment_line;
+                        break;
+                    }
                 } else {
                     break;
                 }
             }
-            Ok(KeyValuePair {
-                key: key_res,
-                value: Value::new(&value_string),
-            })
+
+            // A sequence or set runs until its brackets balance, however the lines it continues
+            // on are indented. A statement of its own ends it regardless.
+            while key_res != Symbol::End && open_brackets(&value_string) > 0 {
+                let Some(line) = self.value_line_after(self.pos) else {
+                    break;
+                };
+                value_string.push(' ');
+                value_string += self.content[line.clone()].trim();
+                self.pos = line.end;
+                if !self.is_eof() {
+                    self.jump(1)?;
+                }
+            }
+
+            // A comment closing out the line isn't part of the value. Block terminators only
+            // carry a name, so theirs is dropped.
+            let value_text = strip_trailing_comment(&value_string);
+            let trailing_comment = match key_res {
+                Symbol::GroupEnd | Symbol::ObjectEnd | Symbol::End => None,
+                _ => Some(comments_within(&value_string[value_text.len()..]).join(" "))
+                    .filter(|c| !c.is_empty()),
+            };
+
+            let mut kvp = KeyValuePair::new(key_res, Value::new(value_text));
+            kvp.trailing_comment = trailing_comment;
+            kvp.span = start..self.statement_end().max(start);
+            Ok(kvp)
         }
     }
 
@@ -640,7 +2161,7 @@ impl PvlReader {
                         _ => group.properties.push(kvp),
                     }
                 } else {
-                    self.next_char()?;
+                    self.skip_line()?;
                 }
             }
 
@@ -672,16 +2193,100 @@ impl PvlReader {
                         _ => object.properties.push(kvp),
                     }
                 } else {
-                    self.next_char()?;
+                    self.skip_line()?;
                 }
             }
 
             Ok(object)
         }
     }
+
+    /// Parses the content from the current caret position through `END` (or end of file)
+    pub fn read_pvl(&mut self) -> Result<Pvl, Error> {
+        let mut pvl = Pvl {
+            properties: vec![],
+            groups: vec![],
+            objects: vec![],
+        };
+
+        while !self.is_eof() && !self.is_at_end() {
+            let line_start = self.pos;
+            if self.is_at_multiline_comment_start()? {
+                self.skip_multiline_comment()?;
+            } else if self.is_at_line_start()? && !self.is_blank_line()? {
+                if self.is_at_group()? {
+                    pvl.groups.push(self.read_group()?);
+                } else if self.is_at_object()? {
+                    pvl.objects.push(self.read_object()?);
+                } else {
+                    let kvp = self.read_key_value_pair_raw()?;
+                    if kvp.key == Symbol::End {
+                        break;
+                    }
+                    pvl.properties.push(kvp)
+                }
+            }
+            if self.pos == line_start {
+                // A line holding no statement is skipped, so the caret always advances
+                self.skip_line()?;
+            } else if !self.is_eof() && !self.is_at_end() {
+                self.jump_to_next_line()?;
+            }
+        }
+        Ok(pvl)
+    }
+}
+
+/// Iterator over the KEY = VALUE statements of a PvlReader. Stops at `END` or end of file.
+/// After yielding an error the iterator is exhausted.
+pub struct Pairs<'r, 'a> {
+    reader: &'r mut PvlReader<'a>,
+    done: bool,
+}
+
+impl Iterator for Pairs<'_, '_> {
+    type Item = Result<KeyValuePair, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        next_pair(self.reader, &mut self.done)
+    }
+}
+
+/// Owning counterpart of `Pairs`, returned by `PvlReader::into_pairs`
+pub struct IntoPairs<'a> {
+    reader: PvlReader<'a>,
+    done: bool,
+}
+
+impl Iterator for IntoPairs<'_> {
+    type Item = Result<KeyValuePair, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        next_pair(&mut self.reader, &mut self.done)
+    }
+}
+
+/// Reads the next statement for the pair iterators, marking them done at `END`, end of file or
+/// the first error
+fn next_pair(reader: &mut PvlReader, done: &mut bool) -> Option<Result<KeyValuePair, Error>> {
+    if *done {
+        return None;
+    }
+    match reader.read_next_pair() {
+        Ok(Some(kvp)) => Some(Ok(kvp)),
+        Ok(None) => {
+            *done = true;
+            None
+        }
+        Err(why) => {
+            *done = true;
+            Some(Err(why))
+        }
+    }
 }
 
 /// The primary user-facing PVL structure
+#[derive(Debug)]
 pub struct Pvl {
     pub properties: Vec<KeyValuePair>,
     pub groups: Vec<Group>,
@@ -712,7 +2317,7 @@ impl Pvl {
     pub fn load(file_path: &Path) -> Result<Self, Error> {
         match fs::read(file_path) {
             Ok(b) => match String::from_utf8_lossy(&b) {
-                Cow::Borrowed(s) => Pvl::from_string(&s),
+                Cow::Borrowed(s) => Pvl::from_string(s),
                 Cow::Owned(s) => Pvl::from_string(&s),
             },
             Err(why) => Err(Error::General(t!(why))),
@@ -740,73 +2345,32 @@ impl Pvl {
     /// }
     /// ```
     pub fn from_string(content: &str) -> Result<Self, Error> {
-        let mut pvl = Pvl {
-            properties: vec![],
-            groups: vec![],
-            objects: vec![],
-        };
-
-        let mut reader = PvlReader::new(content);
-
-        while !reader.is_eof() && !reader.is_at_end() {
-            if reader.is_at_multiline_comment_start().unwrap() {
-                let _ = reader.skip_multiline_comment().unwrap();
-            } else if reader.is_at_line_start().unwrap() && !reader.is_blank_line().unwrap() {
-                if reader.is_at_group().unwrap() {
-                    pvl.groups.push(reader.read_group().unwrap());
-                } else if reader.is_at_object().unwrap() {
-                    pvl.objects.push(reader.read_object().unwrap());
-                } else if let Ok(kvp) = reader.read_key_value_pair_raw() {
-                    if kvp.key == Symbol::End {
-                        break;
-                    } else {
-                        pvl.properties.push(kvp.clone())
-                    }
-                }
-            }
-            if !reader.is_eof() && !reader.is_at_end() {
-                reader.jump_to_next_line()?;
-            }
-        }
-        Ok(pvl)
+        PvlReader::new(content).read_pvl()
     }
 
     pub fn has_property(&self, name: &str) -> bool {
+        self.properties.iter().any(|p| match &p.key {
+            Symbol::Key(n) | Symbol::Pointer(n) => n == name,
+            _ => false,
+        })
+    }
+
+    pub fn get_property(&self, name: &str) -> Option<KeyValuePair> {
         self.properties
             .iter()
-            .filter(|p| match &p.key {
+            .find(|p| match &p.key {
                 Symbol::Key(n) | Symbol::Pointer(n) => n == name,
                 _ => false,
             })
-            .collect::<Vec<&KeyValuePair>>()
-            .len()
-            > 0
-    }
-
-    pub fn get_property(&self, name: &str) -> Option<KeyValuePair> {
-        if self.has_property(name) {
-            Some(
-                self.properties
-                    .iter()
-                    .filter(|p| match &p.key {
-                        Symbol::Key(n) | Symbol::Pointer(n) => n == name,
-                        _ => false,
-                    })
-                    .next()
-                    .unwrap()
-                    .to_owned(),
-            )
-        } else {
-            None
-        }
+            .cloned()
     }
 
     pub fn get_group(&self, name: &str) -> Option<&Group> {
-        self.groups.iter().filter(|g| g.name() == name).next()
+        self.groups.iter().find(|g| g.name() == name)
     }
 
     pub fn get_object(&self, name: &str) -> Option<&Object> {
-        self.objects.iter().filter(|o| o.name() == name).next()
+        self.objects.iter().find(|o| o.name() == name)
     }
 }
 
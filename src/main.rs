@@ -0,0 +1,66 @@
+use pvl::PvlDocument;
+use std::{env, process::ExitCode};
+
+const USAGE: &str = "Usage:
+  pvl to-json <file>          Print the label as JSON
+  pvl dump <file>             Print the label's tree
+  pvl get <file> <key-path>   Print a single value, e.g. IMAGE.LINES";
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<String>>();
+    let args = args.iter().map(|a| a.as_str()).collect::<Vec<&str>>();
+    let (command, file, key_path) = match args.as_slice() {
+        ["to-json", file] | ["dump", file] => (args[0], *file, None),
+        ["get", file, key_path] => ("get", *file, Some(*key_path)),
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let doc = match PvlDocument::from_file(file) {
+        Ok(doc) => doc,
+        Err(why) => {
+            eprintln!("Failed to read {}: {:?}", file, why);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match (command, key_path) {
+        ("to-json", _) => to_json(&doc),
+        ("get", Some(key_path)) => match doc.get(key_path) {
+            Some(value) => {
+                println!("{}", value);
+                ExitCode::SUCCESS
+            }
+            None => {
+                eprintln!("{} not found in {}", key_path, file);
+                ExitCode::FAILURE
+            }
+        },
+        _ => {
+            print!("{}", doc.to_pvl_string());
+            ExitCode::SUCCESS
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+fn to_json(doc: &PvlDocument) -> ExitCode {
+    match serde_json::to_string_pretty(doc) {
+        Ok(json) => {
+            println!("{}", json);
+            ExitCode::SUCCESS
+        }
+        Err(why) => {
+            eprintln!("Failed to serialize: {}", why);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(not(feature = "serde"))]
+fn to_json(_: &PvlDocument) -> ExitCode {
+    eprintln!("to-json needs pvl to be built with the serde feature");
+    ExitCode::FAILURE
+}
@@ -0,0 +1,180 @@
+use crate::{fnv1a_64, Error, PvlDocument, PvlNode, PvlReader, ReaderOptions};
+use rayon::prelude::*;
+use std::ops::Range;
+
+/// Returns the name of the GROUP/OBJECT keyword a statement line starts with, if any, given the
+/// character separating keys from values
+fn block_keyword(line: &str, assignment: char) -> Option<&str> {
+    let symbol = line.split(assignment).next().unwrap_or_default().trim();
+    match symbol {
+        "GROUP" | "OBJECT" | "END_GROUP" | "END_OBJECT" | "END" => Some(symbol),
+        _ => None,
+    }
+}
+
+/// Splits label text into pieces that can be parsed on their own, each ending just after a
+/// top-level `END_OBJECT` line. Statements and comments before an object go with it, and the
+/// last piece holds whatever follows the last object, including `END` and anything after it.
+/// Only the line structure is looked at, skipping the insides of quoted strings and block
+/// comments, so the pieces are cheap to find.
+fn split_top_level_objects(content: &str, assignment: char) -> Vec<Range<usize>> {
+    let mut pieces = vec![];
+    let mut start = 0;
+    let mut line_start = 0;
+    let mut depth = 0_usize;
+    let mut in_quotes = false;
+    let mut in_comment = false;
+    for line in content.split_inclusive('\n') {
+        let line_end = line_start + line.len();
+        if !in_quotes && !in_comment {
+            match block_keyword(line, assignment) {
+                Some("GROUP") | Some("OBJECT") => depth += 1,
+                Some("END_GROUP") => depth = depth.saturating_sub(1),
+                Some("END_OBJECT") => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        pieces.push(start..line_end);
+                        start = line_end;
+                    }
+                }
+                // Nothing after END belongs to the label
+                Some("END") if depth == 0 => break,
+                _ => {}
+            }
+        }
+        if in_comment {
+            in_comment = !line.contains("*/");
+        } else {
+            in_quotes ^= line.matches('"').count() % 2 == 1;
+            if !in_quotes {
+                in_comment = line
+                    .rfind("/*")
+                    .is_some_and(|open| !line[open..].contains("*/"));
+            }
+        }
+        line_start = line_end;
+    }
+    pieces.push(start..content.len());
+    pieces
+}
+
+/// Moves the spans and line numbers of a document parsed from a piece of a label to where the
+/// piece sits in the whole label
+fn shift_positions(document: &mut PvlDocument, bytes: usize, lines: usize) {
+    for node in document.nodes.iter_mut() {
+        match node {
+            PvlNode::Pair(kvp) => {
+                kvp.span = kvp.span.start + bytes..kvp.span.end + bytes;
+            }
+            PvlNode::Block(group) => {
+                group.span = group.span.start + bytes..group.span.end + bytes;
+                group.lines = (group.lines.0 + lines, group.lines.1 + lines);
+                shift_positions(&mut group.document, bytes, lines);
+            }
+        }
+    }
+}
+
+/// Moves the line number an error collected from a piece of a label names, as in
+/// `Line 3: ...`, to where the piece sits in the whole label
+fn shift_error(why: Error, lines: usize) -> Error {
+    let Error::Syntax(text) = &why else {
+        return why;
+    };
+    let numbered = text
+        .strip_prefix("Line ")
+        .and_then(|rest| rest.split_once(": "))
+        .and_then(|(line, message)| Some((line.parse::<usize>().ok()?, message)));
+    match numbered {
+        Some((line, message)) => Error::Syntax(format!("Line {}: {}", line + lines, message)),
+        None => why,
+    }
+}
+
+impl PvlReader<'_> {
+    /// Returns a reader over a piece of the content, with the same settings as this one
+    fn piece_reader<'p>(&self, piece: &'p str) -> PvlReader<'p> {
+        let mut reader = PvlReader::new(piece);
+        reader.assignment = self.assignment;
+        reader.continuation_indent = self.continuation_indent;
+        reader.lenient = self.lenient;
+        // The hash is taken once over the whole label instead
+        reader.options = ReaderOptions {
+            compute_hash: false,
+            ..self.options.clone()
+        };
+        reader.collected_errors = self.collected_errors.as_ref().map(|_| vec![]);
+        reader
+    }
+
+    /// Same as `parse_document`, but parses the top-level OBJECT blocks in parallel, as
+    /// `PvlDocument::parse_parallel` does. Each piece is read with this reader's settings:
+    /// its options, lenient mode, assignment character and continuation indent.
+    /// # Example
+    /// ```
+    /// use pvl::PvlReader;
+    ///
+    /// let mut reader = PvlReader::new("OBJECT : IMAGE\n  LINES : 1024\nEND_OBJECT : IMAGE\nEND\n");
+    /// reader.set_assignment_char(':').unwrap();
+    /// let doc = reader.parse_document_parallel().unwrap();
+    /// assert_eq!(doc.get("IMAGE.LINES").unwrap().parse_u32().unwrap(), 1024);
+    /// ```
+    pub fn parse_document_parallel(&mut self) -> Result<PvlDocument, Error> {
+        let start = self.pos;
+        let content = &self.content[start..];
+        let pieces = split_top_level_objects(content, self.assignment);
+
+        let parsed = pieces
+            .par_iter()
+            .map(|piece| {
+                let mut reader = self.piece_reader(&content[piece.clone()]);
+                let document = reader.parse_document();
+                (document, reader.pos, reader.collected_errors)
+            })
+            .collect::<Vec<_>>();
+
+        let mut document = PvlDocument::default();
+        let mut end_pos = self.content.len();
+        for (piece, (result, piece_pos, errors)) in pieces.iter().zip(parsed) {
+            let offset = start + piece.start;
+            let lines = self.content[..offset].matches('\n').count();
+            if let (Some(collected), Some(errors)) = (self.collected_errors.as_mut(), errors) {
+                collected.extend(errors.into_iter().map(|why| shift_error(why, lines)));
+            }
+            let mut part = result.map_err(|why| shift_error(why, lines))?;
+            shift_positions(&mut part, offset, lines);
+            document.nodes.append(&mut part.nodes);
+            end_pos = offset + piece_pos;
+            if let Some(end) = part.label_end {
+                document.label_end = Some(self.source_offset(offset + end));
+                break;
+            }
+        }
+        self.pos = end_pos;
+        if self.options.compute_hash {
+            let end = self.statement_end().max(start);
+            self.content_hash = Some(fnv1a_64(&self.content.as_bytes()[start..end]));
+        }
+        Ok(document)
+    }
+}
+
+impl PvlDocument {
+    /// Parses a label the same way as `PvlDocument::parse`, but splits it at its top-level
+    /// OBJECT blocks first and parses those in parallel. Worthwhile for large labels, or
+    /// several product labels concatenated together. Nodes are kept in source order. When
+    /// more than one piece fails to parse, the error of the first is returned.
+    /// # Example
+    /// ```
+    /// use pvl::PvlDocument;
+    ///
+    /// let label = "OBJECT = IMAGE\n  LINES = 1024\nEND_OBJECT = IMAGE\nOBJECT = TABLE\n  ROWS = 3\nEND_OBJECT = TABLE\nEND\n";
+    /// let doc = PvlDocument::parse_parallel(label).unwrap();
+    /// assert_eq!(doc.to_pvl_string(), PvlDocument::parse(label).unwrap().to_pvl_string());
+    /// ```
+    pub fn parse_parallel(content: &str) -> Result<Self, Error> {
+        // Line endings are normalized once up front so the pieces line up with what a
+        // sequential parse sees
+        PvlReader::new(content).parse_document_parallel()
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{PvlDocument, ValueType};
+
+/// Keys a label is expected to have, with the type each value should be detected as
+/// # Example
+/// ```
+/// use pvl::{PvlDocument, Schema, SchemaError, ValueType};
+///
+/// let schema = Schema::new()
+///     .required("IMAGE.LINES", ValueType::Integer)
+///     .optional("IMAGE.NOTE", ValueType::String);
+/// let doc = PvlDocument::parse("OBJECT = IMAGE\n  NOTE = 12\nEND_OBJECT = IMAGE\nEND\n").unwrap();
+/// let errors = doc.validate(&schema).unwrap_err();
+/// assert_eq!(errors[0], SchemaError::Missing("IMAGE.LINES".to_owned()));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Schema {
+    /// Path of each key, the type its value must have, and whether the key must be present
+    pub fields: Vec<(String, ValueType, bool)>,
+}
+
+/// A way in which a document doesn't match a `Schema`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaError {
+    /// A required key isn't in the document
+    Missing(String),
+    /// The key's value was detected as a different type than the schema expects
+    TypeMismatch {
+        path: String,
+        expected: ValueType,
+        found: ValueType,
+    },
+}
+
+impl Schema {
+    pub fn new() -> Self {
+        Schema::default()
+    }
+
+    /// Adds a key that must be present with a value of the given type
+    pub fn required(mut self, path: &str, value_type: ValueType) -> Self {
+        self.fields.push((path.to_owned(), value_type, true));
+        self
+    }
+
+    /// Adds a key that may be absent, but must have a value of the given type when present
+    pub fn optional(mut self, path: &str, value_type: ValueType) -> Self {
+        self.fields.push((path.to_owned(), value_type, false));
+        self
+    }
+}
+
+impl PvlDocument {
+    /// Checks the document against a schema, returning every missing required key and type
+    /// mismatch in the order the schema lists them. Only the first value of a repeated key is
+    /// checked, and types must match exactly, so an integer doesn't satisfy `ValueType::Float`.
+    pub fn validate(&self, schema: &Schema) -> Result<(), Vec<SchemaError>> {
+        let errors = schema
+            .fields
+            .iter()
+            .filter_map(|(path, expected, required)| match self.get(path) {
+                None if *required => Some(SchemaError::Missing(path.to_owned())),
+                Some(value) if value.value_type() != *expected => Some(SchemaError::TypeMismatch {
+                    path: path.to_owned(),
+                    expected: *expected,
+                    found: value.value_type(),
+                }),
+                _ => None,
+            })
+            .collect::<Vec<SchemaError>>();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
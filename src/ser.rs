@@ -0,0 +1,120 @@
+use crate::{KeyValuePair, PvlDocument, PvlNode, Symbol, Value, ValueType};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+
+/// A value or a nested block, as it appears under a key of the serialized document
+enum Entry<'a> {
+    Value(&'a Value),
+    Block(&'a PvlDocument),
+}
+
+impl Serialize for Entry<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Entry::Value(v) => v.serialize(serializer),
+            Entry::Block(d) => d.serialize(serializer),
+        }
+    }
+}
+
+/// Serializes typed scalars as JSON-style numbers, booleans and strings, and arrays as
+/// sequences. Units are dropped. Anything that doesn't parse as its detected type falls back
+/// to its raw text.
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.value_type {
+            ValueType::Integer => match self.parse_i64() {
+                Ok(i) => serializer.serialize_i64(i),
+                Err(_) => serializer.serialize_str(&self.value_raw),
+            },
+            ValueType::Float => match self.parse_f64() {
+                Ok(f) => serializer.serialize_f64(f),
+                Err(_) => serializer.serialize_str(&self.value_raw),
+            },
+            ValueType::Bool => match self.as_bool() {
+                Ok(b) => serializer.serialize_bool(b),
+                Err(_) => serializer.serialize_str(&self.value_raw),
+            },
+            ValueType::String => match self.parse_string() {
+                Ok(s) => serializer.serialize_str(&s),
+                Err(_) => serializer.serialize_str(&self.value_raw),
+            },
+            ValueType::Array => match self.parse_array() {
+                Ok(elements) => {
+                    let mut seq = serializer.serialize_seq(Some(elements.len()))?;
+                    for e in elements.iter() {
+                        seq.serialize_element(e)?;
+                    }
+                    seq.end()
+                }
+                Err(_) => serializer.serialize_str(&self.value_raw),
+            },
+            _ => serializer.serialize_str(&self.value_raw),
+        }
+    }
+}
+
+impl Value {
+    /// Converts the value on its own to JSON, as it would appear in a serialized document
+    /// # Example
+    /// ```
+    /// use pvl::Value;
+    ///
+    /// assert_eq!(Value::new("(1, 2.5)").to_json_value(), serde_json::json!([1, 2.5]));
+    /// ```
+    pub fn to_json_value(&self) -> serde_json::Value {
+        serde_json::to_value(self)
+            .unwrap_or_else(|_| serde_json::Value::String(self.value_raw.clone()))
+    }
+}
+
+/// Serializes as a single entry map of the key to its value
+impl Serialize for KeyValuePair {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry(&self.key.value().unwrap_or_default(), &self.value)?;
+        map.end()
+    }
+}
+
+/// Groups the entries of a document by name in source order. Blocks are named after the
+/// group/object and pairs after their key. A name used more than once collects every node.
+pub(crate) fn named_nodes(document: &PvlDocument) -> Vec<(&str, Vec<&PvlNode>)> {
+    let mut entries: Vec<(&str, Vec<&PvlNode>)> = vec![];
+    for node in document.nodes.iter() {
+        let name = match node {
+            PvlNode::Pair(kvp) => match &kvp.key {
+                Symbol::Key(k) | Symbol::Pointer(k) => k.as_str(),
+                _ => continue,
+            },
+            PvlNode::Block(g) => g.name.as_str(),
+        };
+        match entries.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, nodes)) => nodes.push(node),
+            None => entries.push((name, vec![node])),
+        }
+    }
+    entries
+}
+
+/// Serializes as a map in source order. Groups and objects become nested maps, and a key that
+/// appears more than once becomes a sequence of all of its values.
+impl Serialize for PvlDocument {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let entries = named_nodes(self);
+        let mut map = serializer.serialize_map(Some(entries.len()))?;
+        for (name, nodes) in entries.iter() {
+            let values = nodes
+                .iter()
+                .map(|n| match n {
+                    PvlNode::Pair(kvp) => Entry::Value(&kvp.value),
+                    PvlNode::Block(g) => Entry::Block(&g.document),
+                })
+                .collect::<Vec<Entry>>();
+            match values.as_slice() {
+                [single] => map.serialize_entry(name, single)?,
+                _ => map.serialize_entry(name, &values)?,
+            }
+        }
+        map.end()
+    }
+}
@@ -0,0 +1,169 @@
+use crate::{PvlDocument, PvlNode, Symbol, Value, ValueType, DEFAULT_CONTINUATION_INDENT};
+
+/// Formatting options for `PvlDocument::to_pvl_string_with`
+#[derive(Debug, Clone)]
+pub struct WriterOptions {
+    /// Number of spaces each level of GROUP/OBJECT nesting is indented by
+    pub indent: usize,
+    /// Column the continuation lines of a wrapped array start at. Reading the text back with a
+    /// narrower column than `DEFAULT_CONTINUATION_INDENT` needs
+    /// `PvlReader::set_continuation_indent`.
+    pub continuation_column: usize,
+    /// Arrays that would make a line longer than this are wrapped onto continuation lines
+    pub line_width: usize,
+    /// Separates each GROUP/OBJECT from the statement before it with a blank line, as
+    /// canonical PDS labels do
+    pub blank_line_before_blocks: bool,
+    /// Writes the `^` pointers of each GROUP/OBJECT (and of the top level) before its other
+    /// statements, as PDS3 labels conventionally do. Pointers keep their relative order.
+    pub pointers_first: bool,
+}
+
+impl Default for WriterOptions {
+    fn default() -> Self {
+        WriterOptions {
+            indent: 2,
+            continuation_column: DEFAULT_CONTINUATION_INDENT,
+            line_width: 80,
+            blank_line_before_blocks: false,
+            pointers_first: false,
+        }
+    }
+}
+
+impl PvlDocument {
+    /// Serializes the document back to PVL text, terminated by `END`
+    /// # Example
+    /// ```
+    /// use pvl::PvlDocument;
+    ///
+    /// let doc = PvlDocument::parse("OBJECT = IMAGE\n  LINES = 1024\nEND_OBJECT = IMAGE\nEND\n").unwrap();
+    /// assert!(doc.to_pvl_string().starts_with("OBJECT = IMAGE\n"));
+    /// ```
+    pub fn to_pvl_string(&self) -> String {
+        self.to_pvl_string_with(&WriterOptions::default())
+    }
+
+    /// Serializes the document back to PVL text laid out like a canonical PDS label, with a
+    /// blank line before each GROUP/OBJECT
+    /// # Example
+    /// ```
+    /// use pvl::PvlDocument;
+    ///
+    /// let doc = PvlDocument::parse("LINES = 1\nOBJECT = IMAGE\nEND_OBJECT = IMAGE\nEND\n").unwrap();
+    /// assert!(doc.to_pretty_string().starts_with("LINES = 1\n\nOBJECT = IMAGE\n"));
+    /// ```
+    pub fn to_pretty_string(&self) -> String {
+        self.to_pvl_string_with(&WriterOptions {
+            blank_line_before_blocks: true,
+            ..Default::default()
+        })
+    }
+
+    /// Serializes the document back to PVL text using the given formatting options
+    /// # Example
+    /// ```
+    /// use pvl::{PvlDocument, WriterOptions};
+    ///
+    /// let doc = PvlDocument::parse("OBJECT = IMAGE\n  LINES = 1024\nEND_OBJECT = IMAGE\nEND\n").unwrap();
+    /// let options = WriterOptions { indent: 4, ..Default::default() };
+    /// assert!(doc.to_pvl_string_with(&options).contains("\n    LINES = 1024\n"));
+    /// ```
+    pub fn to_pvl_string_with(&self, options: &WriterOptions) -> String {
+        let mut out = String::new();
+        self.write_nodes(&mut out, 0, options);
+        out.push_str("END\n");
+        out
+    }
+
+    /// Writes each node of this document, indented to the given nesting depth
+    fn write_nodes(&self, out: &mut String, depth: usize, options: &WriterOptions) {
+        let indent = " ".repeat(depth * options.indent);
+        let mut nodes = self.nodes.iter().collect::<Vec<&PvlNode>>();
+        if options.pointers_first {
+            // A stable sort, so pointers and the other statements each keep their order
+            nodes.sort_by_key(
+                |n| !matches!(n, PvlNode::Pair(kvp) if matches!(kvp.key, Symbol::Pointer(_))),
+            );
+        }
+        for (i, node) in nodes.into_iter().enumerate() {
+            match node {
+                PvlNode::Pair(kvp) => {
+                    let key = match &kvp.key {
+                        // Keys that wouldn't read back as a single symbol are quoted
+                        Symbol::Key(k) if k.contains(|c: char| c.is_whitespace() || c == '=') => {
+                            format!("\"{}\"", k)
+                        }
+                        Symbol::Key(k) | Symbol::Pointer(k) => k.to_owned(),
+                        _ => continue,
+                    };
+                    let line_start = format!("{}{} = ", indent, key);
+                    out.push_str(&line_start);
+                    write_value(out, line_start.len(), &kvp.value, options);
+                    if let Some(comment) = kvp.trailing_comment() {
+                        out.push_str(&format!(" /* {} */", comment));
+                    }
+                    out.push('\n');
+                }
+                PvlNode::Block(g) => {
+                    // A block opening its parent is already set apart by the parent's keyword
+                    if options.blank_line_before_blocks && i > 0 {
+                        out.push('\n');
+                    }
+                    out.push_str(&format!("{}{} = {}\n", indent, g.kind.keyword(), g.name));
+                    g.document.write_nodes(out, depth + 1, options);
+                    out.push_str(&format!(
+                        "{}{} = {}\n",
+                        indent,
+                        g.kind.end_keyword(),
+                        g.name
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// Writes a value that starts at the given column. An array that would run past the line width
+/// is broken between elements onto continuation lines.
+fn write_value(out: &mut String, column: usize, value: &Value, options: &WriterOptions) {
+    let text = value.to_pvl();
+    if value.value_type != ValueType::Array || column + text.len() <= options.line_width {
+        out.push_str(&text);
+        return;
+    }
+    let elements = match value.parse_array() {
+        // A continuation line can't contain '=', so those arrays stay on one line
+        Ok(e) if !e.iter().any(|e| e.to_pvl().contains('=')) => e,
+        _ => {
+            out.push_str(&text);
+            return;
+        }
+    };
+
+    let trimmed = text.trim();
+    let (open, close) = (&trimmed[..1], &trimmed[trimmed.len() - 1..]);
+    let continuation = " ".repeat(options.continuation_column);
+    let mut line = open.to_owned();
+    let mut line_column = column;
+    let last = elements.len().saturating_sub(1);
+    for (i, e) in elements.iter().enumerate() {
+        let piece = format!("{}{}", e.to_pvl(), if i == last { close } else { "," });
+        let at_line_start = line == open;
+        let separator = if at_line_start { "" } else { " " };
+        if !at_line_start && line_column + line.len() + 1 + piece.len() > options.line_width {
+            out.push_str(&line);
+            out.push('\n');
+            out.push_str(&continuation);
+            line_column = options.continuation_column;
+            line = piece;
+        } else {
+            line.push_str(separator);
+            line.push_str(&piece);
+        }
+    }
+    if elements.is_empty() {
+        line.push_str(close);
+    }
+    out.push_str(&line);
+}
@@ -0,0 +1,45 @@
+use pvl::*;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+/// Counts the allocations made by the current thread
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|a| a.set(a.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(|a| a.get())
+}
+
+#[test]
+fn test_reader_borrows_content_without_allocating() {
+    let label = String::from("PDS_VERSION_ID = PDS3\nLINES = 1024\nEND\n");
+
+    let before = allocations();
+    let reader = PvlReader::new(&label);
+    assert_eq!(allocations(), before);
+
+    assert_eq!(reader.char_at(0).unwrap(), 'P');
+
+    // CRLF content has to be filtered, so that copy is expected
+    let crlf = String::from("LINES = 1024\r\nEND\r\n");
+    let before = allocations();
+    let _reader = PvlReader::new(&crlf);
+    assert!(allocations() > before);
+}
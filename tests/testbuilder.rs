@@ -0,0 +1,60 @@
+use pvl::*;
+
+#[test]
+fn test_builder_nested_document() {
+    let doc = PvlBuilder::new()
+        .key("PDS_VERSION_ID", "PDS3")
+        .key("^IMAGE", 3)
+        .begin_object("IMAGE")
+        .key("LINES", 1024)
+        .key("SCALE", 0.5)
+        .key("VALID", true)
+        .key("FILTERS", vec!["RED", "GREEN"])
+        .begin_group("STATS")
+        .key("MEAN", 12.25)
+        .end_group()
+        .end_object()
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        doc.get("PDS_VERSION_ID").unwrap().parse_string().unwrap(),
+        "PDS3"
+    );
+    assert_eq!(doc.get("IMAGE.LINES").unwrap().parse_i64().unwrap(), 1024);
+    assert_eq!(doc.get("IMAGE.SCALE").unwrap().parse_f64().unwrap(), 0.5);
+    assert!(doc.get("IMAGE.VALID").unwrap().as_bool().unwrap());
+    assert_eq!(
+        doc.get("IMAGE.STATS.MEAN").unwrap().parse_f64().unwrap(),
+        12.25
+    );
+
+    let text = doc.to_pvl_string();
+    assert!(text.contains("\n^IMAGE = 3\n"));
+    assert!(text.contains("\nOBJECT = IMAGE\n"));
+    assert!(text.contains("\n  FILTERS = (\"RED\", \"GREEN\")\n"));
+    assert!(text.contains("\n  GROUP = STATS\n    MEAN = 12.25\n  END_GROUP = STATS\n"));
+
+    let reparsed = PvlDocument::parse(&text).unwrap();
+    let filters = reparsed
+        .get("IMAGE.FILTERS")
+        .unwrap()
+        .parse_array()
+        .unwrap();
+    assert_eq!(filters[1].parse_string().unwrap(), "GREEN");
+    assert_eq!(
+        reparsed.get("IMAGE.SCALE").unwrap().parse_f64().unwrap(),
+        0.5
+    );
+}
+
+#[test]
+fn test_builder_unbalanced() {
+    assert!(PvlBuilder::new().begin_group("IMAGE").build().is_err());
+    assert!(PvlBuilder::new().end_group().build().is_err());
+    assert!(PvlBuilder::new()
+        .begin_object("IMAGE")
+        .end_group()
+        .build()
+        .is_err());
+}
@@ -0,0 +1,51 @@
+use std::process::Command;
+
+const FIXTURE: &str = "tests/testdata/cassini/vims/v1883935188_1.lbl";
+
+fn pvl(args: &[&str]) -> (bool, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_pvl"))
+        .args(args)
+        .output()
+        .expect("Failed to run the pvl binary");
+    (
+        output.status.success(),
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+    )
+}
+
+#[test]
+fn test_cli_get() {
+    let (ok, out) = pvl(&["get", FIXTURE, "SPECTRAL_QUBE.CORE_ITEMS"]);
+    assert!(ok);
+    assert_eq!(out.trim(), "(30, 352, 30)");
+
+    let (ok, out) = pvl(&["get", FIXTURE, "NOT.A.KEY"]);
+    assert!(!ok);
+    assert!(out.is_empty());
+}
+
+#[test]
+fn test_cli_dump() {
+    let (ok, out) = pvl(&["dump", FIXTURE]);
+    assert!(ok);
+    assert!(out.contains("OBJECT = SPECTRAL_QUBE\n"));
+    assert!(out.ends_with("END\n"));
+}
+
+#[test]
+fn test_cli_usage() {
+    assert!(!pvl(&[]).0);
+    assert!(!pvl(&["get", FIXTURE]).0);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_cli_to_json() {
+    let (ok, out) = pvl(&["to-json", FIXTURE]);
+    assert!(ok);
+    let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(
+        json["SPECTRAL_QUBE"]["CORE_ITEMS"],
+        serde_json::json!([30, 352, 30])
+    );
+}
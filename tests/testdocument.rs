@@ -0,0 +1,797 @@
+use pvl::*;
+use std::path::Path;
+
+const NESTED_LABEL: &str = "PDS_VERSION_ID = PDS3
+LINES = 3
+OBJECT = IMAGE
+  LINES = 1024
+  GROUP = STATS
+    MEAN = 12.5
+  END_GROUP = STATS
+END_OBJECT = IMAGE
+END
+";
+
+#[test]
+fn test_document_get_top_level() {
+    let doc = PvlDocument::parse(NESTED_LABEL).unwrap();
+    assert_eq!(doc.get("LINES").unwrap().parse_u32().unwrap(), 3);
+}
+
+#[test]
+fn test_document_get_nested() {
+    let doc = PvlDocument::parse(NESTED_LABEL).unwrap();
+    assert_eq!(doc.get("IMAGE/LINES").unwrap().parse_u32().unwrap(), 1024);
+    assert_eq!(
+        doc.get("IMAGE/STATS/MEAN").unwrap().parse_f64().unwrap(),
+        12.5
+    );
+
+    let image = doc.get_group("IMAGE").unwrap();
+    assert_eq!(image.get("LINES").unwrap().parse_u32().unwrap(), 1024);
+}
+
+#[test]
+fn test_document_get_missing() {
+    let doc = PvlDocument::parse(NESTED_LABEL).unwrap();
+    assert!(doc.get("IMAGE/SAMPLES").is_none());
+    assert!(doc.get("NOPE/LINES").is_none());
+    assert!(doc.get("STATS/MEAN").is_none());
+    assert!(doc.get_group("STATS").is_none());
+}
+
+#[test]
+fn test_document_parse_fixture() {
+    let s = std::fs::read_to_string(Path::new("tests/testdata/cassini/vims/v1883935188_1.lbl"))
+        .unwrap();
+    let doc = PvlDocument::parse(&s).unwrap();
+    assert!(doc.get("SPECTRAL_QUBE/SAMPLE_SUFFIX/SUFFIX_NAME").is_some());
+}
+
+#[test]
+fn test_document_paths_matching() {
+    let doc = PvlDocument::parse(
+        "LINES = 1
+GROUP = IMAGE
+  LINES = 1024
+  SAMPLES = 512
+END_GROUP = IMAGE
+OBJECT = THUMBNAIL
+  LINES = 64
+END_OBJECT = THUMBNAIL
+END
+",
+    )
+    .unwrap();
+
+    let lines = doc.paths_matching("*.LINES");
+    let paths = lines.iter().map(|(p, _)| p.as_str()).collect::<Vec<&str>>();
+    assert_eq!(paths, vec!["IMAGE.LINES", "THUMBNAIL.LINES"]);
+    assert_eq!(lines[1].1.parse_u32().unwrap(), 64);
+
+    assert_eq!(doc.paths_matching("IMAGE.*").len(), 2);
+    assert_eq!(doc.paths_matching("LINES").len(), 1);
+    assert!(doc.paths_matching("*.BANDS").is_empty());
+}
+
+#[test]
+fn test_document_get_all_duplicates() {
+    let doc = PvlDocument::parse(
+        "NOTE = \"first\"
+LINES = 1024
+NOTE = \"second\"
+END
+",
+    )
+    .unwrap();
+
+    let notes = doc.get_all("NOTE");
+    assert_eq!(notes.len(), 2);
+    assert_eq!(notes[1].parse_string().unwrap(), "second");
+    assert_eq!(doc.get("NOTE").unwrap().parse_string().unwrap(), "first");
+    assert!(doc.get_all("MISSING").is_empty());
+}
+
+#[test]
+fn test_document_get_ignore_case() {
+    let doc = PvlDocument::parse(
+        "GROUP = Image
+  Lines = 1024
+END_GROUP = Image
+END
+",
+    )
+    .unwrap();
+
+    assert!(doc.get("IMAGE/LINES").is_none());
+    assert!(doc.get("Image/Lines").is_some());
+    assert_eq!(
+        doc.get_ignore_case("IMAGE/LINES")
+            .unwrap()
+            .parse_u32()
+            .unwrap(),
+        1024
+    );
+    assert!(doc.get_ignore_case("image/lines").is_some());
+}
+
+#[test]
+fn test_document_insert_creates_groups() {
+    let mut doc = PvlDocument::default();
+    doc.insert("IMAGE.STATS.MEAN", Value::new("12.5")).unwrap();
+    doc.insert("IMAGE.LINES", Value::new("1024")).unwrap();
+
+    assert_eq!(doc.nodes.len(), 1);
+    let image = doc.get_group("IMAGE").unwrap();
+    assert!(image.get_group("STATS").is_some());
+    assert_eq!(
+        doc.get("IMAGE.STATS.MEAN").unwrap().parse_f64().unwrap(),
+        12.5
+    );
+    assert_eq!(doc.get("IMAGE.LINES").unwrap().parse_u32().unwrap(), 1024);
+
+    // Replaces rather than duplicating
+    doc.insert("IMAGE.STATS.MEAN", Value::new("3.0")).unwrap();
+    assert_eq!(doc.get_all("IMAGE.STATS.MEAN").len(), 1);
+    assert_eq!(
+        doc.get("IMAGE.STATS.MEAN").unwrap().parse_f64().unwrap(),
+        3.0
+    );
+
+    assert!(doc.insert("", Value::new("1")).is_err());
+}
+
+/// Collects the dotted path of every key in a document, in order
+fn collect_key_paths(doc: &PvlDocument, prefix: &str, paths: &mut Vec<String>) {
+    for node in doc.nodes.iter() {
+        match node {
+            PvlNode::Pair(kvp) => {
+                if let Some(k) = kvp.key.value() {
+                    paths.push(format!("{}{}", prefix, k));
+                }
+            }
+            PvlNode::Block(g) => {
+                collect_key_paths(&g.document, &format!("{}{}.", prefix, g.name), paths)
+            }
+        }
+    }
+}
+
+#[test]
+fn test_parse_keys_only_matches_full_parse() {
+    for path in [
+        "tests/testdata/cassini/vims/v1883935188_1.lbl",
+        "tests/testdata/msl/mahli/3423MH0002970011201599C00_DRCX.LBL",
+    ] {
+        let s = std::fs::read_to_string(Path::new(path)).unwrap();
+        let mut expected = vec![];
+        collect_key_paths(&PvlDocument::parse(&s).unwrap(), "", &mut expected);
+
+        let keys = parse_keys_only(&s).unwrap();
+        assert!(!keys.is_empty());
+        assert_eq!(keys, expected);
+    }
+}
+
+#[test]
+fn test_document_terminator_trailing_comment() {
+    let label = "GROUP = IMAGE
+  LINES = 1024
+END_GROUP = IMAGE /* x */
+SPACECRAFT_NAME = MARS_SCIENCE_LABORATORY
+END /* done */
+";
+    let doc = PvlDocument::parse(label).unwrap();
+    assert_eq!(doc.get("IMAGE/LINES").unwrap().parse_u32().unwrap(), 1024);
+    assert_eq!(
+        doc.get("SPACECRAFT_NAME").unwrap().parse_flag().unwrap(),
+        "MARS_SCIENCE_LABORATORY"
+    );
+    assert!(doc.get("IMAGE/SPACECRAFT_NAME").is_none());
+
+    // The terminator's name still has to match its block
+    assert!(PvlDocument::parse("GROUP = IMAGE\nEND_GROUP = CAMERA /* x */\nEND\n").is_err());
+}
+
+#[test]
+fn test_document_validate_required() {
+    let doc = PvlDocument::parse(NESTED_LABEL).unwrap();
+    assert!(doc.validate_required(&["IMAGE/LINES"]).is_ok());
+
+    let missing = doc
+        .validate_required(&["PRODUCT_ID", "IMAGE/LINES", "IMAGE.INSTRUMENT_ID"])
+        .unwrap_err();
+    assert_eq!(missing, vec!["PRODUCT_ID", "IMAGE.INSTRUMENT_ID"]);
+}
+
+#[test]
+fn test_document_from_file() {
+    let doc = PvlDocument::from_file("tests/testdata/msl/mahli/3423MH0002970011201599C00_DRCX.LBL")
+        .unwrap();
+    assert!(doc.get("IMAGE/LINES").is_some());
+
+    match PvlDocument::from_file("tests/testdata/does_not_exist.lbl") {
+        Err(Error::Io(_)) => {}
+        other => panic!("Expected an IO error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_document_scope_at_line() {
+    let doc = PvlDocument::parse(NESTED_LABEL).unwrap();
+    assert!(doc.scope_at_line(2).is_empty());
+    assert_eq!(doc.scope_at_line(4), vec!["IMAGE"]);
+    assert_eq!(doc.scope_at_line(6), vec!["IMAGE", "STATS"]);
+    assert_eq!(doc.scope_at_line(7), vec!["IMAGE", "STATS"]);
+    assert_eq!(doc.scope_at_line(8), vec!["IMAGE"]);
+    assert!(doc.scope_at_line(9).is_empty());
+
+    match &doc.nodes[2] {
+        PvlNode::Block(g) => assert_eq!(g.lines(), (3, 8)),
+        other => panic!("Expected the IMAGE block, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_document_group_span() {
+    let doc = PvlDocument::parse(NESTED_LABEL).unwrap();
+    let block = |document: &PvlDocument, name: &str| -> PvlGroup {
+        document
+            .nodes
+            .iter()
+            .find_map(|n| match n {
+                PvlNode::Block(g) if g.name == name => Some(g.clone()),
+                _ => None,
+            })
+            .unwrap()
+    };
+
+    let image = block(&doc, "IMAGE");
+    assert!(NESTED_LABEL[image.span()].starts_with("OBJECT = IMAGE\n"));
+    assert!(NESTED_LABEL[image.span()].ends_with("\nEND_OBJECT = IMAGE"));
+
+    let stats = block(&image.document, "STATS");
+    assert_eq!(
+        &NESTED_LABEL[stats.span()],
+        "  GROUP = STATS\n    MEAN = 12.5\n  END_GROUP = STATS"
+    );
+}
+
+#[test]
+fn test_document_max_nesting_depth() {
+    let nested = |depth: usize| -> String {
+        let mut label = String::new();
+        for i in 0..depth {
+            label.push_str(&format!("GROUP = G{}\n", i));
+        }
+        label.push_str("LINES = 1\n");
+        for i in (0..depth).rev() {
+            label.push_str(&format!("END_GROUP = G{}\n", i));
+        }
+        label.push_str("END\n");
+        label
+    };
+
+    match PvlDocument::parse(&nested(1000)) {
+        Err(Error::Syntax(why)) => assert_eq!(why, "max nesting depth exceeded"),
+        other => panic!("Expected a syntax error, got {:?}", other),
+    }
+    assert!(PvlDocument::parse(&nested(DEFAULT_MAX_DEPTH)).is_ok());
+
+    let label = nested(3);
+    let mut reader = PvlReader::new(&label);
+    reader.set_options(ReaderOptions {
+        max_depth: 2,
+        ..Default::default()
+    });
+    assert!(reader.parse_document().is_err());
+}
+
+#[test]
+fn test_document_parse_lenient() {
+    let label = "PDS_VERSION_ID = PDS3
+LINES = 1024
+THIS LINE IS BROKEN
+OBJECT = IMAGE
+  SAMPLES = 512
+END_OBJECT = IMAGE
+BANDS = 3
+END
+";
+    assert!(PvlDocument::parse(label).is_ok());
+
+    let (doc, errors) = PvlDocument::parse_lenient(label);
+    assert_eq!(errors.len(), 1);
+    match &errors[0] {
+        Error::Syntax(why) => assert!(why.starts_with("Line 3:")),
+        other => panic!("Expected a syntax error, got {:?}", other),
+    }
+    assert_eq!(doc.get("LINES").unwrap().parse_u32().unwrap(), 1024);
+    assert_eq!(doc.get("IMAGE.SAMPLES").unwrap().parse_u32().unwrap(), 512);
+    assert_eq!(doc.get("BANDS").unwrap().parse_u32().unwrap(), 3);
+    assert_eq!(doc.nodes.len(), 4);
+
+    let (doc, errors) = PvlDocument::parse_lenient(
+        "A = 1\nEND_GROUP = X\nGROUP = G\n  B = 2\nEND_GROUP = H\nC = 3\nEND\n",
+    );
+    assert_eq!(errors.len(), 2);
+    assert!(doc.get("G.B").is_some() && doc.get("C").is_some());
+}
+
+#[test]
+fn test_document_iter_groups() {
+    let doc = PvlDocument::parse(
+        "GROUP = PROCESSING
+  VERSION = 2
+END_GROUP = PROCESSING
+OBJECT = IMAGE
+  LINES = 1024
+  GROUP = STATS
+    MEAN = 12.5
+  END_GROUP = STATS
+END_OBJECT = IMAGE
+END
+",
+    )
+    .unwrap();
+
+    let groups = doc
+        .iter_groups()
+        .map(|(path, g)| (path, g.kind))
+        .collect::<Vec<(String, BlockKind)>>();
+    assert_eq!(
+        groups,
+        vec![
+            ("PROCESSING".to_owned(), BlockKind::Group),
+            ("IMAGE".to_owned(), BlockKind::Object),
+            ("IMAGE.STATS".to_owned(), BlockKind::Group),
+        ]
+    );
+    let (_, stats) = doc.iter_groups().last().unwrap();
+    assert!(stats.document.get("MEAN").is_some());
+}
+
+#[test]
+fn test_document_capture_comments() {
+    let label = "PDS_VERSION_ID = PDS3
+/* Image dimensions */
+/* in pixels */
+OBJECT = IMAGE
+  /* explanation */
+  LINES = 1024
+  SAMPLES = 512
+END_OBJECT = IMAGE
+END
+";
+    let pair = |doc: &PvlDocument, key: &str| -> KeyValuePair {
+        doc.get_group("IMAGE")
+            .unwrap()
+            .nodes
+            .iter()
+            .find_map(|n| match n {
+                PvlNode::Pair(kvp) if kvp.key == Symbol::Key(key.to_owned()) => Some(kvp.clone()),
+                _ => None,
+            })
+            .unwrap()
+    };
+
+    let mut reader = PvlReader::new(label);
+    reader.set_options(ReaderOptions {
+        capture_comments: true,
+        ..Default::default()
+    });
+    let doc = reader.parse_document().unwrap();
+    assert_eq!(pair(&doc, "LINES").comments(), ["explanation"]);
+    assert!(pair(&doc, "SAMPLES").comments().is_empty());
+
+    let doc = PvlDocument::parse(label).unwrap();
+    assert!(pair(&doc, "LINES").comments().is_empty());
+}
+
+#[test]
+fn test_document_data_offset() {
+    // Attached label with CRLF line endings, padded out to its records and followed by data
+    let label = "PDS_VERSION_ID = PDS3\r\nRECORD_BYTES = 64\r\nLINES = 2\r\nEND\r\n";
+    let mut bytes = label.as_bytes().to_vec();
+    bytes.resize(64, b' ');
+    bytes.extend([0xFF, 0x00, 0x80, 0x7F]);
+    let doc = PvlDocument::parse(&String::from_utf8_lossy(&bytes)).unwrap();
+    assert_eq!(doc.data_offset(), Some(64));
+    assert_eq!(bytes[doc.data_offset().unwrap()], 0xFF);
+
+    // Without RECORD_BYTES the data starts right after the END line
+    let doc = PvlDocument::parse("LINES = 2\r\nEND\r\n").unwrap();
+    assert_eq!(doc.data_offset(), Some(16));
+
+    // A byte order mark counts towards the offset
+    let bytes = b"\xEF\xBB\xBFA = 1\r\nEND\nDATA";
+    let doc = PvlReader::from_bytes(bytes)
+        .unwrap()
+        .parse_document()
+        .unwrap();
+    assert_eq!(&bytes[doc.data_offset().unwrap()..], b"DATA");
+
+    // LABEL_RECORDS takes precedence over where END happens to be
+    let doc = PvlDocument::parse("RECORD_BYTES = 100\nLABEL_RECORDS = 3\nEND\n").unwrap();
+    assert_eq!(doc.data_offset(), Some(300));
+
+    assert_eq!(
+        PvlDocument::parse("LINES = 2\n").unwrap().data_offset(),
+        None
+    );
+}
+
+#[test]
+fn test_document_root_name() {
+    let label = "LINES = 1024\nGROUP = STATS\n  MEAN = 12.5\nEND_GROUP = STATS\nEND\n";
+    let doc = PvlReader::new(label).parse_document_named("IMAGE").unwrap();
+    assert_eq!(doc.name(), Some("IMAGE"));
+    assert_eq!(doc.prefix_keys(), vec!["IMAGE.LINES", "IMAGE.STATS.MEAN"]);
+    // Lookups and the written label don't involve the root name
+    assert!(doc.get("STATS.MEAN").is_some());
+    assert_eq!(
+        doc.to_pvl_string(),
+        PvlDocument::parse(label).unwrap().to_pvl_string()
+    );
+
+    let mut doc = PvlDocument::parse(label).unwrap();
+    assert_eq!(doc.name(), None);
+    assert_eq!(doc.prefix_keys(), vec!["LINES", "STATS.MEAN"]);
+    doc.set_name(Some("CAMERA"));
+    assert_eq!(doc.prefix_keys()[0], "CAMERA.LINES");
+}
+
+#[test]
+fn test_document_nameless_block() {
+    let label = "LINES = 1\nGROUP\n  MEAN = 12.5\nEND_GROUP = STATS\nOBJECT =\nEND_OBJECT\nEND\n";
+    match PvlDocument::parse(label) {
+        Err(Error::Syntax(why)) => assert_eq!(why, "GROUP without a name"),
+        other => panic!("Expected a syntax error, got {:?}", other),
+    }
+
+    let mut reader = PvlReader::new(label);
+    reader.set_lenient(true);
+    let doc = reader.parse_document().unwrap();
+    assert_eq!(
+        doc.iter_groups()
+            .map(|(path, _)| path)
+            .collect::<Vec<String>>(),
+        vec!["UNNAMED_GROUP_2", "UNNAMED_OBJECT_5"]
+    );
+    assert!(doc.get("UNNAMED_GROUP_2.MEAN").is_some());
+}
+
+#[test]
+fn test_document_validate_schema() {
+    let schema = Schema::new()
+        .required("PRODUCT_ID", ValueType::String)
+        .required("IMAGE.LINES", ValueType::Integer)
+        .required("IMAGE.SAMPLES", ValueType::Integer)
+        .optional("IMAGE.SCALING_FACTOR", ValueType::Float)
+        .optional("IMAGE.OFFSET", ValueType::Float);
+    let doc = PvlDocument::parse(
+        "PRODUCT_ID = \"N_001\"
+OBJECT = IMAGE
+  SAMPLES = 512
+  SCALING_FACTOR = HIGH
+END_OBJECT = IMAGE
+END
+",
+    )
+    .unwrap();
+    assert_eq!(
+        doc.validate(&schema).unwrap_err(),
+        vec![
+            SchemaError::Missing("IMAGE.LINES".to_owned()),
+            SchemaError::TypeMismatch {
+                path: "IMAGE.SCALING_FACTOR".to_owned(),
+                expected: ValueType::Float,
+                found: ValueType::Flag,
+            }
+        ]
+    );
+
+    let doc = PvlDocument::parse(
+        "PRODUCT_ID = \"N_001\"
+OBJECT = IMAGE
+  LINES = 1024
+  SAMPLES = 512
+END_OBJECT = IMAGE
+END
+",
+    )
+    .unwrap();
+    assert!(doc.validate(&schema).is_ok());
+}
+
+#[test]
+fn test_document_duplicate_blocks() {
+    let doc = PvlDocument::parse(
+        "GROUP = BAND
+  NUMBER = 1
+END_GROUP = BAND
+GROUP = BAND
+  NUMBER = 2
+END_GROUP = BAND
+OBJECT = IMAGE
+  GROUP = STATS
+  END_GROUP = STATS
+  GROUP = STATS
+  END_GROUP = STATS
+  GROUP = STATS
+  END_GROUP = STATS
+  GROUP = BAND
+  END_GROUP = BAND
+END_OBJECT = IMAGE
+OBJECT = TABLE
+  GROUP = STATS
+  END_GROUP = STATS
+END_OBJECT = TABLE
+LINES = 1
+LINES = 2
+END
+",
+    )
+    .unwrap();
+    assert_eq!(doc.duplicate_blocks(), vec!["BAND", "IMAGE.STATS"]);
+    assert!(PvlDocument::parse("GROUP = A\nEND_GROUP = A\nEND\n")
+        .unwrap()
+        .duplicate_blocks()
+        .is_empty());
+}
+
+#[test]
+fn test_document_merge() {
+    let mut base = PvlDocument::parse(
+        "PRODUCT_ID = N_001
+NOTE = \"first\"
+NOTE = \"second\"
+OBJECT = IMAGE
+  LINES = 1024
+  SAMPLES = 512
+END_OBJECT = IMAGE
+END
+",
+    )
+    .unwrap();
+    let overlay = PvlDocument::parse(
+        "OBJECT = IMAGE
+  LINES = 2048
+END_OBJECT = IMAGE
+GROUP = PROCESSING
+  VERSION = 2
+END_GROUP = PROCESSING
+NOTE = \"patched\"
+END
+",
+    )
+    .unwrap();
+
+    let mut appended = base.clone();
+    base.merge(&overlay);
+    assert_eq!(base.get("IMAGE.LINES").unwrap().parse_u32().unwrap(), 2048);
+    assert_eq!(base.get("IMAGE.SAMPLES").unwrap().parse_u32().unwrap(), 512);
+    assert_eq!(
+        base.get("PROCESSING.VERSION").unwrap().parse_u32().unwrap(),
+        2
+    );
+    assert_eq!(
+        base.get_all("NOTE")
+            .iter()
+            .map(|v| v.parse_string().unwrap())
+            .collect::<Vec<String>>(),
+        vec!["patched"]
+    );
+    // The replaced key keeps its place
+    assert!(
+        matches!(&base.nodes[1], PvlNode::Pair(kvp) if kvp.key == Symbol::Key("NOTE".to_owned()))
+    );
+    assert_eq!(
+        base.iter_groups()
+            .map(|(path, _)| path)
+            .collect::<Vec<String>>(),
+        vec!["IMAGE", "PROCESSING"]
+    );
+
+    appended.merge_with(&overlay, MergeMode::Append);
+    assert_eq!(appended.get_all("NOTE").len(), 3);
+    assert_eq!(appended.get_all("IMAGE.LINES").len(), 2);
+}
+
+#[test]
+fn test_document_merge_block_kinds() {
+    let mut base = PvlDocument::parse(
+        "OBJECT = IMAGE
+  LINES = 1024
+END_OBJECT = IMAGE
+END
+",
+    )
+    .unwrap();
+    let overlay = PvlDocument::parse(
+        "GROUP = IMAGE
+  LINES = 2048
+END_GROUP = IMAGE
+END
+",
+    )
+    .unwrap();
+
+    base.merge(&overlay);
+    let blocks = base
+        .nodes
+        .iter()
+        .filter_map(|n| match n {
+            PvlNode::Block(g) => Some((g.kind, g.name.as_str())),
+            _ => None,
+        })
+        .collect::<Vec<(BlockKind, &str)>>();
+    assert_eq!(
+        blocks,
+        vec![(BlockKind::Object, "IMAGE"), (BlockKind::Group, "IMAGE")]
+    );
+    assert_eq!(base.get("IMAGE.LINES").unwrap().parse_u32().unwrap(), 1024);
+}
+
+#[test]
+fn test_document_diff_ignores_wrapping() {
+    for path in [
+        "tests/testdata/cassini/vims/v1883935188_1.lbl",
+        "tests/testdata/msl/navcam/NRB_701384494RAD_F0933408NCAM00200M1.LBL",
+        "tests/testdata/msl/mcam/3423MR1016960081600825C00_DRCX.LBL",
+    ] {
+        let doc = PvlDocument::from_file(path).unwrap();
+        let reparsed = PvlDocument::parse(&doc.to_pvl_string()).unwrap();
+        assert_eq!(doc.diff(&reparsed), vec![], "{}", path);
+    }
+
+    let wrapped = PvlDocument::parse("BANDS = (RED,\n  GREEN, BLUE)\nEND\n").unwrap();
+    let sequence = PvlDocument::parse("BANDS = (RED, GREEN, BLUE)\nEND\n").unwrap();
+    let set = PvlDocument::parse("BANDS = {RED, GREEN, BLUE}\nEND\n").unwrap();
+    assert!(wrapped.diff(&sequence).is_empty());
+    assert_eq!(sequence.diff(&set).len(), 1);
+}
+
+#[test]
+fn test_document_diff() {
+    let label = "PRODUCT_ID = N_001
+NOTE = \"first\"
+OBJECT = IMAGE
+  LINES = 1024
+  SAMPLES = 512
+END_OBJECT = IMAGE
+END
+";
+    let old = PvlDocument::parse(label).unwrap();
+    let new = PvlDocument::parse(&label.replace("1024", "2048")).unwrap();
+    assert_eq!(
+        old.diff(&new),
+        vec![Change::Modified {
+            key_path: "IMAGE.LINES".to_owned(),
+            old: Value::new("1024"),
+            new: Value::new("2048"),
+        }]
+    );
+    assert!(old.diff(&old).is_empty());
+
+    let new = PvlDocument::parse(
+        "PRODUCT_ID = N_001
+NOTE = \"first\"
+NOTE = \"second\"
+OBJECT = IMAGE
+  LINES = 1024
+END_OBJECT = IMAGE
+END
+",
+    )
+    .unwrap();
+    assert_eq!(
+        old.diff(&new),
+        vec![
+            Change::Removed {
+                key_path: "IMAGE.SAMPLES".to_owned(),
+                value: Value::new("512"),
+            },
+            Change::Added {
+                key_path: "NOTE".to_owned(),
+                value: Value::new("\"second\""),
+            }
+        ]
+    );
+}
+
+#[test]
+fn test_document_walk_order() {
+    let label = "PDS_VERSION_ID = PDS3
+OBJECT = IMAGE
+  LINES = 1024
+  GROUP = STATS
+    MEAN = 12.5
+  END_GROUP = STATS
+  SAMPLES = 512
+END_OBJECT = IMAGE
+^IMAGE = 3
+NOTE = \"last\"
+END
+";
+    let doc = PvlDocument::parse(label).unwrap();
+    let mut visited = vec![];
+    doc.walk(|path, kvp| visited.push((path.join("."), kvp.value.to_pvl())));
+    assert_eq!(
+        visited,
+        vec![
+            ("PDS_VERSION_ID".to_owned(), "PDS3".to_owned()),
+            ("IMAGE.LINES".to_owned(), "1024".to_owned()),
+            ("IMAGE.STATS.MEAN".to_owned(), "12.5".to_owned()),
+            ("IMAGE.SAMPLES".to_owned(), "512".to_owned()),
+            ("^IMAGE".to_owned(), "3".to_owned()),
+            ("NOTE".to_owned(), "\"last\"".to_owned()),
+        ]
+    );
+
+    let top = doc
+        .iter()
+        .map(|n| match n {
+            PvlNode::Pair(kvp) => kvp.key.value().unwrap_or_default(),
+            PvlNode::Block(g) => g.name.clone(),
+        })
+        .collect::<Vec<String>>();
+    assert_eq!(top, vec!["PDS_VERSION_ID", "IMAGE", "^IMAGE", "NOTE"]);
+}
+
+#[test]
+fn test_document_keys() {
+    let doc = PvlDocument::parse(
+        "PDS_VERSION_ID = PDS3
+^IMAGE = 3
+OBJECT = IMAGE
+  LINES = 1024
+END_OBJECT = IMAGE
+NOTE = \"first\"
+NOTE = \"second\"
+END
+",
+    )
+    .unwrap();
+    assert!(doc.contains_key("PDS_VERSION_ID"));
+    assert!(doc.contains_key("IMAGE/LINES"));
+    assert!(!doc.contains_key("IMAGE"));
+    assert!(!doc.contains_key("SAMPLES"));
+    assert_eq!(doc.keys(), vec!["PDS_VERSION_ID", "^IMAGE", "NOTE"]);
+    assert!(PvlDocument::default().keys().is_empty());
+}
+
+#[test]
+fn test_document_type_histogram() {
+    let doc = PvlDocument::parse(
+        "PDS_VERSION_ID = PDS3
+NOTE = \"mixed\"
+OBJECT = IMAGE
+  LINES = 1024
+  SAMPLES = 512
+  SCALING_FACTOR = 0.5
+  FILTERS = (RED, GREEN)
+  GROUP = EMPTY
+  END_GROUP = EMPTY
+END_OBJECT = IMAGE
+ODD = ???
+END
+",
+    )
+    .unwrap();
+    assert_eq!(doc.len(), 7);
+    assert!(!doc.is_empty());
+
+    let histogram = doc.type_histogram();
+    assert_eq!(histogram.len(), 6);
+    assert_eq!(histogram[&ValueType::Integer], 2);
+    assert_eq!(histogram[&ValueType::Undetermined], 1);
+    assert_eq!(histogram[&ValueType::Flag], 1);
+    assert_eq!(histogram[&ValueType::String], 1);
+    assert_eq!(histogram[&ValueType::Float], 1);
+    assert_eq!(histogram[&ValueType::Array], 1);
+
+    let empty = PvlDocument::parse("GROUP = A\nEND_GROUP = A\nEND\n").unwrap();
+    assert!(empty.is_empty());
+    assert!(empty.type_histogram().is_empty());
+}
@@ -0,0 +1,145 @@
+#![cfg(feature = "parallel")]
+
+use pvl::*;
+
+/// Collects the name, source lines and span of every block, depth first
+fn block_positions(doc: &PvlDocument) -> Vec<(String, (usize, usize), std::ops::Range<usize>)> {
+    let mut positions = vec![];
+    for node in doc.nodes.iter() {
+        if let PvlNode::Block(g) = node {
+            positions.push((g.name.clone(), g.lines(), g.span()));
+            positions.extend(block_positions(&g.document));
+        }
+    }
+    positions
+}
+
+fn assert_same_parse(content: &str) {
+    let sequential = PvlDocument::parse(content).unwrap();
+    let parallel = PvlDocument::parse_parallel(content).unwrap();
+    assert_eq!(parallel.to_pvl_string(), sequential.to_pvl_string());
+    assert_eq!(block_positions(&parallel), block_positions(&sequential));
+    assert_eq!(parallel.data_offset(), sequential.data_offset());
+}
+
+#[test]
+fn test_parallel_matches_sequential() {
+    let fixture = std::fs::read_to_string("tests/testdata/cassini/vims/v1883935188_1.lbl").unwrap();
+    assert_same_parse(&fixture);
+
+    let label = "PDS_VERSION_ID = PDS3\r
+/* The first object */\r
+OBJECT = IMAGE\r
+  LINES = 1024\r
+  GROUP = STATS\r
+    MEAN = 12.5\r
+  END_GROUP = STATS\r
+  NOTE = \"A note spanning\r
+END_OBJECT = IMAGE\r
+  two lines\"\r
+END_OBJECT = IMAGE\r
+GROUP = PROCESSING\r
+  VERSION = 2\r
+END_GROUP = PROCESSING\r
+OBJECT = TABLE\r
+  ROWS = 3\r
+END_OBJECT = TABLE\r
+END\r
+OBJECT = NOT_PART_OF_THE_LABEL\r
+";
+    assert_same_parse(label);
+
+    let doc = PvlDocument::parse_parallel(label).unwrap();
+    assert_eq!(doc.keys(), vec!["PDS_VERSION_ID"]);
+    assert_eq!(
+        doc.get("IMAGE.NOTE").unwrap().parse_string().unwrap(),
+        "A note spanning END_OBJECT = IMAGE two lines"
+    );
+    assert_eq!(doc.get("TABLE.ROWS").unwrap().parse_u32().unwrap(), 3);
+}
+
+#[test]
+fn test_parallel_error() {
+    let label =
+        "OBJECT = IMAGE\n  LINES = 1024\nEND_OBJECT = IMAGE\nOBJECT = TABLE\n  ROWS = \"3\nEND\n";
+    assert!(PvlDocument::parse(label).is_err());
+    assert!(PvlDocument::parse_parallel(label).is_err());
+}
+
+#[test]
+fn test_parallel_assignment_char() {
+    let label = "OBJECT : IMAGE\n  LINES : 1024\nEND_OBJECT : IMAGE\nOBJECT : TABLE\n  ROWS : 3\nEND_OBJECT : TABLE\nEND\n";
+    let mut reader = PvlReader::new(label);
+    reader.set_assignment_char(':').unwrap();
+    let sequential = reader.parse_document().unwrap();
+
+    let mut reader = PvlReader::new(label);
+    reader.set_assignment_char(':').unwrap();
+    let parallel = reader.parse_document_parallel().unwrap();
+    assert_eq!(block_positions(&parallel), block_positions(&sequential));
+    assert_eq!(parallel.data_offset(), sequential.data_offset());
+    assert_eq!(
+        parallel.get("IMAGE.LINES").unwrap().parse_u32().unwrap(),
+        1024
+    );
+    assert_eq!(parallel.get("TABLE.ROWS").unwrap().parse_u32().unwrap(), 3);
+}
+
+/// The result of parsing a label and the content hash it left on the reader
+type Parsed = (Result<PvlDocument, Error>, Option<u64>);
+
+/// Parses the label with the same settings sequentially and in parallel
+fn parse_both(label: &str, configure: impl Fn(&mut PvlReader)) -> (Parsed, Parsed) {
+    let mut reader = PvlReader::new(label);
+    configure(&mut reader);
+    let sequential = reader.parse_document();
+    let sequential = (sequential, reader.content_hash());
+
+    let mut reader = PvlReader::new(label);
+    configure(&mut reader);
+    let parallel = reader.parse_document_parallel();
+    (sequential, (parallel, reader.content_hash()))
+}
+
+#[test]
+fn test_parallel_reader_options() {
+    let label = "OBJECT = IMAGE\n  LINES = 1024\n  DESCRIPTION = FIRST PART\n      = MORE TEXT\n\
+                 END_OBJECT = IMAGE\nOBJECT = TABLE\n  ROWS = 3\n  RANGE = 1..5\n\
+                 END_OBJECT = TABLE\nEND\n";
+    let options = ReaderOptions {
+        compute_hash: true,
+        detect_ranges: true,
+        ..Default::default()
+    };
+
+    let ((sequential, sequential_hash), (parallel, parallel_hash)) = parse_both(label, |reader| {
+        reader.set_options(options.clone());
+        reader.set_lenient(true);
+    });
+    let (sequential, parallel) = (sequential.unwrap(), parallel.unwrap());
+    assert_eq!(parallel.to_pvl_string(), sequential.to_pvl_string());
+    assert_eq!(block_positions(&parallel), block_positions(&sequential));
+    assert_eq!(
+        parallel.get("TABLE.RANGE").unwrap().value_type(),
+        ValueType::Range
+    );
+    assert!(sequential_hash.is_some());
+    assert_eq!(parallel_hash, sequential_hash);
+
+    // The stray '=' line is only recovered from in lenient mode
+    let ((sequential, _), (parallel, _)) = parse_both(label, |reader| {
+        reader.set_options(options.clone());
+    });
+    assert!(sequential.is_err());
+    assert!(parallel.is_err());
+
+    let label = "OBJECT = IMAGE\n  STATUS = N/A\nEND_OBJECT = IMAGE\nEND\n";
+    let ((sequential, _), (parallel, _)) = parse_both(label, |reader| {
+        reader.set_options(ReaderOptions {
+            error_on_undetermined: true,
+            ..Default::default()
+        });
+    });
+    assert!(sequential.is_err());
+    assert!(parallel.is_err());
+}
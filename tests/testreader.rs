@@ -0,0 +1,828 @@
+use pvl::*;
+use std::fs;
+
+#[test]
+fn test_reader_reset_reparse() {
+    let s = fs::read_to_string("tests/testdata/msl/mahli/3423MH0002970011201599C00_DRCX.LBL")
+        .expect("Failed to load PVL label");
+    let mut reader = PvlReader::new(&s);
+
+    let first = reader.read_pvl().unwrap();
+
+    reader.reset();
+    assert_eq!(reader.current_char().unwrap(), s.chars().next().unwrap());
+
+    let second = reader.read_pvl().unwrap();
+    assert_eq!(format!("{:?}", first), format!("{:?}", second));
+    assert!(!first.properties.is_empty());
+}
+
+#[test]
+fn test_reader_reset_clears_pass_state() {
+    let mut reader = PvlReader::new("LINES = 1024\nEND\n");
+    reader.set_options(ReaderOptions {
+        compute_hash: true,
+        ..Default::default()
+    });
+    reader.parse_document().unwrap();
+    assert!(reader.content_hash().is_some());
+
+    reader.reset();
+    assert_eq!(reader.content_hash(), None);
+}
+
+#[test]
+fn test_reader_read_pvl_malformed() {
+    let label = "LINES = 1024\nNOTE = \"never closed\nEND\n";
+    let mut reader = PvlReader::new(label);
+    assert!(matches!(reader.read_pvl(), Err(Error::Syntax(_))));
+
+    // The legacy loader used to drop the statement and return the rest of the label
+    assert!(matches!(Pvl::from_string(label), Err(Error::Syntax(_))));
+}
+
+#[test]
+fn test_reader_remaining() {
+    let label = "LINES = 1024\nSAMPLES = 512\nEND\n";
+    let mut reader = PvlReader::new(label);
+    assert_eq!(reader.len(), label.len());
+    assert!(!reader.is_empty());
+    assert_eq!(reader.remaining(), label);
+
+    reader.read_key_value_pair_raw().unwrap();
+    assert_eq!(reader.remaining(), "SAMPLES = 512\nEND\n");
+    reader.read_key_value_pair_raw().unwrap();
+    assert_eq!(reader.remaining(), "END\n");
+    reader.read_key_value_pair_raw().unwrap();
+    assert_eq!(reader.remaining(), "");
+
+    reader.reset();
+    assert_eq!(reader.remaining(), label);
+    let document = reader.parse_document().unwrap();
+    assert_eq!(document.get("SAMPLES").unwrap().parse_u32().unwrap(), 512);
+
+    assert!(PvlReader::new("").is_empty());
+}
+
+#[test]
+fn test_reader_checkpoint_restore() {
+    let label = "LINES = 1024\nSAMPLES = 512\nNOTE = \"°\"\nEND\n";
+    let mut reader = PvlReader::new(label);
+    reader.read_key_value_pair_raw().unwrap();
+
+    let checkpoint = reader.checkpoint();
+    let ahead = reader.read_key_value_pair_raw().unwrap();
+    assert_eq!(ahead.key, Symbol::Key("SAMPLES".to_owned()));
+    reader.read_key_value_pair_raw().unwrap();
+
+    reader.restore(checkpoint).unwrap();
+    assert_eq!(reader.read_key_value_pair_raw().unwrap(), ahead);
+    assert_eq!(
+        reader.read_key_value_pair_raw().unwrap().key,
+        Symbol::Key("NOTE".to_owned())
+    );
+
+    assert!(matches!(
+        reader.restore(label.len() + 1),
+        Err(Error::Programming(_))
+    ));
+    // Inside the two bytes of the degree sign
+    assert!(reader.restore(label.find('°').unwrap() + 1).is_err());
+    reader.restore(label.len()).unwrap();
+    assert!(reader.is_eof());
+}
+
+#[test]
+fn test_reader_pairs_iterator() {
+    let label = "/* Three pairs */\nLINES = 1024\n\nSAMPLES = 512\nBANDS = 1\nEND\n\
+                 /* Nothing after END is part of the label */\n";
+    let mut reader = PvlReader::new(label);
+
+    let pairs = reader
+        .pairs()
+        .collect::<Result<Vec<KeyValuePair>, Error>>()
+        .unwrap();
+    assert_eq!(pairs.len(), 3);
+    assert_eq!(pairs[0].key, Symbol::Key("LINES".to_owned()));
+    assert_eq!(pairs[1].key, Symbol::Key("SAMPLES".to_owned()));
+    assert_eq!(pairs[2].key, Symbol::Key("BANDS".to_owned()));
+    assert_eq!(pairs[1].value.parse_u32().unwrap(), 512);
+}
+
+#[test]
+fn test_reader_into_pairs() {
+    let reader = PvlReader::new("LINES = 1024\n\nSAMPLES = 512\nBANDS = 1\nEND\n");
+
+    // The iterator owns the reader, so it can be moved to another thread
+    let pairs = std::thread::spawn(move || {
+        reader
+            .into_pairs()
+            .collect::<Result<Vec<KeyValuePair>, Error>>()
+    })
+    .join()
+    .unwrap()
+    .unwrap();
+    let keys = pairs
+        .iter()
+        .map(|kvp| kvp.key.clone())
+        .collect::<Vec<Symbol>>();
+    assert_eq!(
+        keys,
+        vec![
+            Symbol::Key("LINES".to_owned()),
+            Symbol::Key("SAMPLES".to_owned()),
+            Symbol::Key("BANDS".to_owned())
+        ]
+    );
+    assert_eq!(pairs[1].value.parse_u32().unwrap(), 512);
+
+    let mut pairs = PvlReader::new("LINES = 1024\n/* never closed").into_pairs();
+    assert!(pairs.next().unwrap().is_ok());
+    assert!(pairs.next().unwrap().is_err());
+    assert!(pairs.next().is_none());
+}
+
+#[test]
+fn test_reader_pairs_iterator_malformed() {
+    // A continuation line without a pair before it to continue
+    let label = format!("{}ORPHANED\nLINES = 1024\nEND\n", " ".repeat(37));
+    let mut reader = PvlReader::new(&label);
+
+    let mut pairs = reader.pairs();
+    assert!(pairs.next().unwrap().is_err());
+    assert!(pairs.next().is_none());
+}
+
+#[test]
+fn test_reader_pairs_unterminated_comment() {
+    let mut reader = PvlReader::new("LINES = 1024\n/* never closed");
+
+    let mut pairs = reader.pairs();
+    assert!(pairs.next().unwrap().is_ok());
+    assert!(pairs.next().unwrap().is_err());
+    assert!(pairs.next().is_none());
+}
+
+#[test]
+fn test_reader_slice_variants_match_owned() {
+    let label = "INSTRUMENT_ID = \"NAV_LEFT_B\"\nLINES = 1024\n";
+
+    let mut owned = PvlReader::new(label);
+    let mut sliced = PvlReader::new(label);
+    for _ in 0..2 {
+        let symbol = owned.read_symbol().unwrap();
+        let value = owned.read_remaining_line().unwrap();
+        owned.skip_line().unwrap();
+
+        assert_eq!(
+            Some(sliced.read_symbol_slice().unwrap().to_owned()),
+            symbol.value()
+        );
+        assert_eq!(sliced.read_remaining_line_slice().unwrap(), value);
+        sliced.skip_line().unwrap();
+    }
+    assert!(sliced.is_eof());
+}
+
+#[test]
+fn test_reader_continuation_skips_comment_line() {
+    let continuation = " ".repeat(37);
+    let label = format!(
+        "BAND_NAMES = (RED,\n{c}GREEN,\n/* Blue comes last */\n{c}BLUE)\nLINES = 1024\nEND\n",
+        c = continuation
+    );
+    let doc = PvlDocument::parse(&label).unwrap();
+
+    let bands = doc.get("BAND_NAMES").unwrap().parse_array().unwrap();
+    let names = bands
+        .iter()
+        .map(|b| b.parse_flag().unwrap())
+        .collect::<Vec<String>>();
+    assert_eq!(names, vec!["RED", "GREEN", "BLUE"]);
+    assert_eq!(doc.get("LINES").unwrap().parse_u32().unwrap(), 1024);
+}
+
+#[test]
+fn test_reader_continuation_indent_20() {
+    let label = format!(
+        "FILTER_NAMES = (RED,\n{c}GREEN,\n{c}BLUE)\nLINES = 1024\nEND\n",
+        c = " ".repeat(20)
+    );
+
+    // The default indent doesn't see the 20 space lines as continuations, but the open
+    // bracket still carries the array on to them
+    assert_eq!(
+        PvlDocument::parse(&label)
+            .unwrap()
+            .get("FILTER_NAMES")
+            .unwrap()
+            .parse_array()
+            .unwrap()
+            .len(),
+        3
+    );
+
+    let mut reader = PvlReader::new(&label);
+    reader.set_continuation_indent(20);
+    let doc = reader.parse_document().unwrap();
+    assert_eq!(
+        doc.get("FILTER_NAMES")
+            .unwrap()
+            .parse_array()
+            .unwrap()
+            .len(),
+        3
+    );
+    assert_eq!(doc.get("LINES").unwrap().parse_u32().unwrap(), 1024);
+}
+
+#[test]
+fn test_reader_continuation_indent_tabs() {
+    // Both continuation lines reach column 20 with tabs advancing to multiples of 8
+    let label = "FILTER_NAMES = (RED,\n\t\t    GREEN,\n  \t\t    BLUE)\nLINES = 1024\nEND\n";
+
+    let mut reader = PvlReader::new(label);
+    reader.set_continuation_indent(20);
+    let doc = reader.parse_document().unwrap();
+    assert_eq!(
+        doc.get("FILTER_NAMES").unwrap().to_pvl(),
+        "(RED,GREEN,BLUE)"
+    );
+}
+
+#[test]
+fn test_reader_continuation_check_at_final_line() {
+    // The final line is far shorter than the continuation indent
+    let mut reader = PvlReader::new("LINES = 1024\nEND");
+    reader.read_key_value_pair_raw().unwrap();
+    assert!(!reader.is_at_value_line_continuation().unwrap());
+    assert_eq!(reader.read_symbol().unwrap(), Symbol::End);
+
+    // A continuation on the final line without a trailing newline still joins the value
+    let label = format!("NAMES = (A,\n{}B)", " ".repeat(37));
+    let mut reader = PvlReader::new(&label);
+    let kvp = reader.read_key_value_pair_raw().unwrap();
+    assert_eq!(kvp.value.to_pvl(), "(A,B)");
+    assert!(reader.is_eof());
+    assert!(!reader.is_at_value_line_continuation().unwrap());
+}
+
+#[test]
+fn test_reader_assignment_spacing() {
+    for label in [
+        "KEY=VALUE\n",
+        "KEY = VALUE\n",
+        "KEY =VALUE\n",
+        "KEY= VALUE\n",
+    ] {
+        let mut reader = PvlReader::new(label);
+        let kvp = reader.read_key_value_pair_raw().unwrap();
+        assert_eq!(kvp.key, Symbol::Key("KEY".to_owned()));
+        assert_eq!(kvp.value.parse_flag().unwrap(), "VALUE");
+    }
+
+    // Only the assignment operator is dropped, not '=' inside the value
+    let mut reader = PvlReader::new("EQUATION = \"E=MC2\"\n");
+    let kvp = reader.read_key_value_pair_raw().unwrap();
+    assert_eq!(kvp.value.to_pvl(), "\"E=MC2\"");
+}
+
+#[test]
+fn test_reader_multiline_quoted_string() {
+    let label = "DESCRIPTION = \"This image was acquired
+    during the extended mission.\"
+LINES = 1024
+END
+";
+    let doc = PvlDocument::parse(label).unwrap();
+    assert_eq!(
+        doc.get("DESCRIPTION").unwrap().parse_string().unwrap(),
+        "This image was acquired during the extended mission."
+    );
+    assert_eq!(doc.get("LINES").unwrap().parse_u32().unwrap(), 1024);
+
+    let mut reader = PvlReader::new("DESCRIPTION = \"Never closed\nLINES = 1\n");
+    assert!(reader.read_key_value_pair_raw().is_err());
+}
+
+#[test]
+fn test_reader_quoted_key() {
+    let mut reader = PvlReader::new("\"A = B\" = 1\n\"MY KEY\" = 5\n");
+    let kvp = reader.read_key_value_pair_raw().unwrap();
+    assert_eq!(kvp.key, Symbol::Key("A = B".to_owned()));
+    assert_eq!(kvp.value.parse_u32().unwrap(), 1);
+
+    let kvp = reader.read_key_value_pair_raw().unwrap();
+    assert_eq!(kvp.key, Symbol::Key("MY KEY".to_owned()));
+    assert_eq!(kvp.value.parse_u32().unwrap(), 5);
+
+    let mut sliced = PvlReader::new("\"A = B\" = 1\n");
+    assert_eq!(sliced.read_symbol_slice().unwrap(), "A = B");
+    assert_eq!(sliced.read_remaining_line_slice().unwrap(), "1");
+
+    // Written back with its quotes so it reads as the same key
+    let doc = PvlDocument::parse("\"A = B\" = 1\nEND\n").unwrap();
+    let text = doc.to_pvl_string();
+    assert!(text.starts_with("\"A = B\" = 1\n"));
+    assert_eq!(
+        PvlDocument::parse(&text)
+            .unwrap()
+            .get("A = B")
+            .unwrap()
+            .parse_u32()
+            .unwrap(),
+        1
+    );
+}
+
+#[test]
+fn test_reader_adjacent_keys_keep_first_char() {
+    let label = "LINES = 1024\nSAMPLES = 512\n/* trailing spaces */  \nBANDS = 1\nEND\n";
+
+    let mut reader = PvlReader::new(label);
+    reader.read_key_value_pair_raw().unwrap();
+    assert_eq!(reader.current_char().unwrap(), 'S');
+    reader.jump_to_next_line().unwrap();
+    assert_eq!(reader.current_char().unwrap(), 'S');
+
+    let pvl = Pvl::from_string(label).unwrap();
+    let keys = pvl
+        .properties
+        .iter()
+        .map(|p| p.key.value().unwrap())
+        .collect::<Vec<String>>();
+    assert_eq!(keys, vec!["LINES", "SAMPLES", "BANDS"]);
+}
+
+#[test]
+fn test_reader_from_bytes_bom() {
+    let mut bytes = vec![0xEF, 0xBB, 0xBF];
+    bytes.extend_from_slice("TARGET_NAME = \"Ø MARS\"\nTEMPÉRATURE = 5\nEND\n".as_bytes());
+
+    let mut reader = PvlReader::from_bytes(&bytes).unwrap();
+    assert_eq!(reader.current_char().unwrap(), 'T');
+    let doc = reader.parse_document().unwrap();
+    assert_eq!(
+        doc.get("TARGET_NAME").unwrap().parse_string().unwrap(),
+        "Ø MARS"
+    );
+    assert_eq!(doc.get("TEMPÉRATURE").unwrap().parse_u32().unwrap(), 5);
+}
+
+#[test]
+#[cfg(not(feature = "latin1"))]
+fn test_reader_from_bytes_invalid_utf8() {
+    // "CAF\xC9" is Latin-1 for CAFÉ
+    let bytes = b"NAME = CAF\xC9\nEND\n";
+    match PvlReader::from_bytes(bytes) {
+        Err(Error::InvalidEncoding(msg)) => assert!(msg.contains("UTF-8")),
+        Err(why) => panic!("Expected an encoding error, got {:?}", why),
+        Ok(_) => panic!("Expected an encoding error"),
+    }
+}
+
+#[test]
+#[cfg(feature = "latin1")]
+fn test_reader_from_bytes_latin1() {
+    let bytes = b"NAME = CAF\xC9\nEND\n";
+    let doc = PvlReader::from_bytes(bytes)
+        .unwrap()
+        .parse_document()
+        .unwrap();
+    assert_eq!(doc.get("NAME").unwrap().to_pvl(), "CAFÉ");
+}
+
+#[test]
+#[cfg(feature = "latin1")]
+fn test_reader_from_bytes_latin1_data_offset() {
+    // Each Latin-1 byte becomes two bytes of UTF-8, which mustn't shift the offset
+    let bytes = b"\xEF\xBB\xBFNAME = \xC9T\xC9\r\nEND\r\nDATA";
+    let doc = PvlReader::from_bytes(bytes)
+        .unwrap()
+        .parse_document()
+        .unwrap();
+    assert_eq!(doc.get("NAME").unwrap().to_pvl(), "ÉTÉ");
+    assert_eq!(&bytes[doc.data_offset().unwrap()..], b"DATA");
+}
+
+#[test]
+fn test_reader_char_and_byte_len() {
+    let reader = PvlReader::new("TEMP = 20 <°C>\nEND\n");
+    assert_eq!(reader.char_len(), 19);
+    assert_eq!(reader.byte_len(), 20);
+
+    // Line endings are normalized before measuring
+    let reader = PvlReader::new("LINES = 1\r\nEND\r\n");
+    assert_eq!(reader.char_len(), 14);
+    assert_eq!(reader.byte_len(), 14);
+}
+
+#[test]
+fn test_reader_lenient_stray_assignment() {
+    let label = "DESCRIPTION = FIRST PART\n    = MORE TEXT\nLINES = 1024\nEND\n";
+
+    match PvlDocument::parse(label) {
+        Err(Error::Syntax(why)) => assert_eq!(why, "empty key before '='"),
+        other => panic!("Expected a syntax error, got {:?}", other),
+    }
+
+    let mut reader = PvlReader::new(label);
+    reader.set_lenient(true);
+    let doc = reader.parse_document().unwrap();
+    assert_eq!(
+        doc.get("DESCRIPTION").unwrap().to_pvl(),
+        "FIRST PART MORE TEXT"
+    );
+    assert_eq!(doc.get("LINES").unwrap().parse_u32().unwrap(), 1024);
+    assert_eq!(doc.nodes.len(), 2);
+}
+
+#[test]
+fn test_reader_error_on_undetermined() {
+    let label = "LINES = 1024\nSTATUS = N/A\nEND\n";
+
+    let doc = PvlDocument::parse(label).unwrap();
+    assert_eq!(
+        doc.get("STATUS").unwrap().value_type(),
+        ValueType::Undetermined
+    );
+
+    let mut reader = PvlReader::new(label);
+    reader.set_options(ReaderOptions {
+        error_on_undetermined: true,
+        ..Default::default()
+    });
+    match reader.parse_document() {
+        Err(Error::Syntax(why)) => assert!(why.contains("STATUS") && why.contains("N/A")),
+        other => panic!("Expected a syntax error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_reader_truncated_input_never_panics() {
+    let label = std::fs::read_to_string("tests/testdata/cassini/vims/v1883935188_1.lbl").unwrap();
+    let boundaries = label.char_indices().map(|(i, _)| i).collect::<Vec<usize>>();
+
+    // A fixed-seed LCG, so any failure can be reproduced
+    let mut seed: u64 = 0x5eed;
+    let mut cuts = (0..400)
+        .map(|_| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            boundaries[(seed >> 33) as usize % boundaries.len()]
+        })
+        .collect::<Vec<usize>>();
+    cuts.extend(0..64);
+
+    for cut in cuts {
+        let truncated = &label[..cut];
+        let _ = PvlDocument::parse(truncated);
+        let _ = PvlReader::new(truncated).read_pvl();
+        let _ = parse_keys_only(truncated);
+        let _ = PvlReader::new(truncated).pairs().count();
+    }
+}
+
+#[test]
+fn test_reader_content_hash() {
+    let label = "PDS_VERSION_ID = PDS3\nLINES = 1024\nEND\n\u{1}\u{2}binary";
+    let hash_of = |text: &str| -> Option<u64> {
+        let mut reader = PvlReader::new(text);
+        reader.set_options(ReaderOptions {
+            compute_hash: true,
+            ..Default::default()
+        });
+        reader.parse_document().unwrap();
+        reader.content_hash()
+    };
+
+    // 64-bit FNV-1a of the label through END, computed independently
+    let region = &label[..label.find("END").unwrap() + 3];
+    let expected = region.bytes().fold(0xcbf29ce484222325_u64, |h, b| {
+        (h ^ b as u64).wrapping_mul(0x100000001b3)
+    });
+    assert_eq!(hash_of(label), Some(expected));
+    assert_eq!(hash_of(label), hash_of(label));
+    assert_ne!(hash_of(label), hash_of("LINES = 1024\nEND\n"));
+
+    let mut reader = PvlReader::new(label);
+    reader.parse_document().unwrap();
+    assert_eq!(reader.content_hash(), None);
+}
+
+#[test]
+fn test_reader_detect_ranges() {
+    let label = "VALID_RANGE = 10:20\nSTART = 14:32\nEND\n";
+    let doc = PvlDocument::parse(label).unwrap();
+    assert_eq!(
+        doc.get("VALID_RANGE").unwrap().value_type(),
+        ValueType::Time
+    );
+
+    let mut reader = PvlReader::new(label);
+    reader.set_options(ReaderOptions {
+        detect_ranges: true,
+        ..Default::default()
+    });
+    let doc = reader.parse_document().unwrap();
+    let range = doc.get("VALID_RANGE").unwrap();
+    assert_eq!(range.value_type(), ValueType::Range);
+    assert_eq!(range.parse_range().unwrap(), (10.0, 20.0));
+}
+
+#[test]
+fn test_reader_pair_span() {
+    let label = "PDS_VERSION_ID = PDS3
+OBJECT = IMAGE
+  LINES = 1024 /* rows */
+  FILTER_NAMES = (RED,
+                                       GREEN)
+END_OBJECT = IMAGE
+END
+";
+    let doc = PvlDocument::parse(label).unwrap();
+    let spans = doc
+        .get_group("IMAGE")
+        .unwrap()
+        .nodes
+        .iter()
+        .filter_map(|n| match n {
+            PvlNode::Pair(kvp) => Some(&label[kvp.span()]),
+            _ => None,
+        })
+        .collect::<Vec<&str>>();
+    assert_eq!(
+        spans,
+        vec![
+            "  LINES = 1024 /* rows */",
+            "  FILTER_NAMES = (RED,\n                                       GREEN)"
+        ]
+    );
+
+    // Patching a value in place through its span
+    let lines = doc.get_group("IMAGE").unwrap().nodes[0].clone();
+    let PvlNode::Pair(kvp) = lines else {
+        panic!("Expected a pair");
+    };
+    let mut patched = label.to_owned();
+    patched.replace_range(kvp.span(), "  LINES = 2048");
+    let doc = PvlDocument::parse(&patched).unwrap();
+    assert_eq!(doc.get("IMAGE.LINES").unwrap().parse_u32().unwrap(), 2048);
+}
+
+#[test]
+fn test_reader_line_endings() {
+    let label = "PDS_VERSION_ID = PDS3
+
+/* Image description */
+OBJECT = IMAGE
+  LINES = 1024
+  NOTE = \"spans
+    two lines\"
+  FILTER_NAMES = (RED,
+                                       GREEN)
+END_OBJECT = IMAGE
+END
+";
+    let lf = PvlDocument::parse(label).unwrap();
+    let crlf = PvlDocument::parse(&label.replace('\n', "\r\n")).unwrap();
+    let cr = PvlDocument::parse(&label.replace('\n', "\r")).unwrap();
+    assert_eq!(lf.to_pvl_string(), crlf.to_pvl_string());
+    assert_eq!(lf.to_pvl_string(), cr.to_pvl_string());
+    assert_eq!(cr.get("IMAGE.LINES").unwrap().parse_u32().unwrap(), 1024);
+    assert_eq!(
+        cr.get("IMAGE.NOTE").unwrap().parse_string().unwrap(),
+        "spans two lines"
+    );
+
+    let lf_keys = parse_keys_only(label).unwrap();
+    assert_eq!(
+        lf_keys,
+        parse_keys_only(&label.replace('\n', "\r\n")).unwrap()
+    );
+    assert_eq!(
+        lf_keys,
+        parse_keys_only(&label.replace('\n', "\r")).unwrap()
+    );
+}
+
+#[test]
+fn test_reader_value_on_next_line() {
+    let label = "PRODUCT_ID = N_001
+DESCRIPTION =
+
+  \"A long description written
+   under its key\"
+SOURCE_IDS =
+  (A, B,
+                                       C)
+EMPTY =
+END_GROUP = X
+LINES = 1024
+END
+";
+    let (doc, errors) = PvlDocument::parse_lenient(label);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        doc.get("DESCRIPTION").unwrap().parse_string().unwrap(),
+        "A long description written under its key"
+    );
+    assert_eq!(
+        doc.get("SOURCE_IDS").unwrap().parse_string_array().unwrap(),
+        vec!["A", "B", "C"]
+    );
+    assert_eq!(doc.get("EMPTY").unwrap().as_str(), "");
+    assert_eq!(doc.get("LINES").unwrap().parse_u32().unwrap(), 1024);
+    assert_eq!(
+        parse_keys_only(label).unwrap(),
+        vec!["PRODUCT_ID", "DESCRIPTION", "SOURCE_IDS", "EMPTY", "LINES"]
+    );
+}
+
+#[test]
+fn test_reader_trailing_comment() {
+    let label = "LINES = 1024 /* full frame */
+BANDS = (1, 2) /* first */ /* second */
+NOTE = \"keeps /* this */\"
+SAMPLES = 512
+END_OBJECT = IMAGE /* dropped */
+END
+";
+    let mut reader = PvlReader::new(label);
+    let lines = reader.read_key_value_pair_raw().unwrap();
+    assert_eq!(lines.value.parse_u32().unwrap(), 1024);
+    assert_eq!(lines.trailing_comment(), Some("full frame"));
+
+    let bands = reader.read_key_value_pair_raw().unwrap();
+    assert_eq!(bands.value.parse_i64_array().unwrap(), vec![1, 2]);
+    assert_eq!(bands.trailing_comment(), Some("first second"));
+
+    let note = reader.read_key_value_pair_raw().unwrap();
+    assert_eq!(note.value.parse_string().unwrap(), "keeps /* this */");
+    assert_eq!(note.trailing_comment(), None);
+
+    assert_eq!(
+        reader.read_key_value_pair_raw().unwrap().trailing_comment(),
+        None
+    );
+    let end = reader.read_key_value_pair_raw().unwrap();
+    assert_eq!(
+        (end.value.as_str(), end.trailing_comment()),
+        ("IMAGE", None)
+    );
+
+    // The writer puts the comment back after the value
+    let doc = PvlDocument::parse(
+        label
+            .replace("END_OBJECT = IMAGE /* dropped */\n", "")
+            .as_str(),
+    )
+    .unwrap();
+    assert!(doc
+        .to_pvl_string()
+        .starts_with("LINES = 1024 /* full frame */\n"));
+}
+
+#[test]
+fn test_reader_require_assignment() {
+    let label = "LINES = 1024\nSTRAY TOKEN\nSAMPLES = 512\nEND\n";
+    let options = ReaderOptions {
+        require_assignment: true,
+        ..Default::default()
+    };
+
+    // By default the line is a key with an empty value
+    let doc = PvlDocument::parse(label).unwrap();
+    assert_eq!(doc.get("STRAY TOKEN").unwrap().as_str(), "");
+
+    let mut reader = PvlReader::new(label);
+    reader.set_options(options.clone());
+    match reader.parse_document() {
+        Err(Error::Syntax(why)) => assert_eq!(why, "expected '=' in assignment"),
+        other => panic!("Expected a syntax error, got {:?}", other),
+    }
+
+    let mut reader = PvlReader::new(label);
+    reader.set_options(options);
+    reader.set_lenient(true);
+    let doc = reader.parse_document().unwrap();
+    assert_eq!(doc.keys(), vec!["LINES", "SAMPLES"]);
+}
+
+#[test]
+fn test_reader_assignment_char() {
+    let label = "LINES : 1024
+START_TIME : 12:30:00
+GROUP : STATS
+  MEAN : 12.5
+END_GROUP : STATS
+END
+";
+    let mut reader = PvlReader::new(label);
+    assert_eq!(reader.assignment_char(), '=');
+    reader.set_assignment_char(':').unwrap();
+    let doc = reader.parse_document().unwrap();
+    assert_eq!(doc.get("LINES").unwrap().parse_u32().unwrap(), 1024);
+    assert_eq!(doc.get("START_TIME").unwrap().to_pvl(), "12:30:00");
+    assert_eq!(doc.get("STATS.MEAN").unwrap().parse_f64().unwrap(), 12.5);
+
+    for c in [
+        ' ', '\t', '"', '\'', '/', '(', ')', '{', '}', ',', '<', '>', 'é',
+    ] {
+        assert!(matches!(
+            reader.set_assignment_char(c),
+            Err(Error::Programming(_))
+        ));
+    }
+    assert_eq!(reader.assignment_char(), ':');
+}
+
+#[test]
+fn test_reader_empty_key() {
+    match PvlDocument::parse("LINES = 1024\n= 5\nEND\n") {
+        Err(Error::Syntax(why)) => assert_eq!(why, "empty key before '='"),
+        other => panic!("Expected a syntax error, got {:?}", other),
+    }
+
+    let mut reader = PvlReader::new("= 5\nEND\n");
+    reader.set_lenient(true);
+    assert!(reader.parse_document().unwrap().is_empty());
+}
+
+#[test]
+fn test_reader_unaligned_array() {
+    let label = "FOO = (1, 2, 3,
+ 4, 5, 6,
+ 7, 8)
+BAR = {\"A\", \"(B\",
+  \"C\"}
+UNCLOSED = (1, 2,
+LINES = 1024
+END
+";
+    let doc = PvlDocument::parse(label).unwrap();
+    let foo = doc.get("FOO").unwrap().parse_array().unwrap();
+    assert_eq!(foo.len(), 8);
+    assert_eq!(foo[7].parse_i64().unwrap(), 8);
+    assert_eq!(doc.get("BAR").unwrap().parse_array().unwrap().len(), 3);
+    assert_eq!(doc.get("UNCLOSED").unwrap().to_pvl(), "(1, 2,");
+    assert_eq!(doc.get("LINES").unwrap().parse_u32().unwrap(), 1024);
+}
+
+#[test]
+fn test_reader_jump_clamps_at_eof() {
+    let mut reader = PvlReader::new("LINES = 1");
+    assert_eq!(reader.jump(5).unwrap(), 5);
+    assert_eq!(reader.jump(10).unwrap(), 4);
+    assert!(reader.is_eof());
+    assert!(matches!(reader.jump(1), Err(Error::Eof)));
+
+    let mut reader = PvlReader::new("/* never closed *");
+    assert!(matches!(
+        reader.skip_multiline_comment(),
+        Err(Error::Syntax(_))
+    ));
+    let mut reader = PvlReader::new("/* closed */");
+    assert_eq!(reader.skip_multiline_comment().unwrap(), " closed ");
+}
+
+#[test]
+fn test_reader_from_reader_stops_at_end() {
+    let mut bytes =
+        b"LINES = 1024\r\nNOTE = \"A note\nEND\nwith END on its own line\"\nEND\r\n".to_vec();
+    let label_len = bytes.len();
+    bytes.extend_from_slice(&[0xFF, 0x00, b'\n', 0xFE]);
+    let mut stream = std::io::Cursor::new(bytes);
+
+    let mut reader = PvlReader::from_reader(&mut stream).unwrap();
+    assert_eq!(stream.position() as usize, label_len);
+    let doc = reader.parse_document().unwrap();
+    assert_eq!(doc.get("LINES").unwrap().parse_u32().unwrap(), 1024);
+    assert_eq!(
+        doc.get("NOTE").unwrap().parse_string().unwrap(),
+        "A note END with END on its own line"
+    );
+    assert_eq!(doc.data_offset(), Some(label_len));
+
+    let mut rest = vec![];
+    std::io::Read::read_to_end(&mut stream, &mut rest).unwrap();
+    assert_eq!(rest, vec![0xFF, 0x00, b'\n', 0xFE]);
+}
+
+#[test]
+fn test_reader_from_reader_end_in_comment() {
+    let label = "/* comment\nEND\n*/\nLINES = 1\nEND\n";
+    let mut stream = std::io::Cursor::new(label.as_bytes());
+
+    let doc = PvlReader::from_reader(&mut stream)
+        .unwrap()
+        .parse_document()
+        .unwrap();
+    assert_eq!(stream.position() as usize, label.len());
+    assert_eq!(doc.get("LINES").unwrap().parse_u32().unwrap(), 1);
+    assert_eq!(
+        doc.to_pvl_string(),
+        PvlDocument::parse(label).unwrap().to_pvl_string()
+    );
+}
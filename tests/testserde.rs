@@ -0,0 +1,152 @@
+#![cfg(feature = "serde")]
+
+use pvl::*;
+use serde::Deserialize;
+use serde_json::json;
+
+#[test]
+fn test_serde_document_to_json() {
+    let label = "PDS_VERSION_ID = PDS3
+NOTE = \"first\"
+NOTE = \"second\"
+OBJECT = IMAGE
+  LINES = 1024
+  SCALING_FACTOR = 0.5
+  VALID = \"TRUE\"
+  BAND_NAMES = (RED, \"GREEN\")
+  GROUP = STATS
+    MEAN = 12.5 <DN>
+  END_GROUP = STATS
+END_OBJECT = IMAGE
+END
+";
+    let doc = PvlDocument::parse(label).unwrap();
+    let value = serde_json::to_value(&doc).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "PDS_VERSION_ID": "PDS3",
+            "NOTE": ["first", "second"],
+            "IMAGE": {
+                "LINES": 1024,
+                "SCALING_FACTOR": 0.5,
+                "VALID": true,
+                "BAND_NAMES": ["RED", "GREEN"],
+                "STATS": {
+                    "MEAN": 12.5
+                }
+            }
+        })
+    );
+
+    // Keys keep their source order
+    let text = serde_json::to_string(&doc).unwrap();
+    assert!(text.starts_with("{\"PDS_VERSION_ID\""));
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+struct Stats {
+    mean: f64,
+    valid: bool,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+struct Image {
+    lines: u32,
+    samples: u32,
+    band_names: Vec<String>,
+    note: Vec<String>,
+    stats: Stats,
+    target: Option<String>,
+}
+
+#[test]
+fn test_serde_from_document() {
+    let label = "LINES = 1024
+SAMPLES = 512
+BAND_NAMES = (RED, \"GREEN\")
+NOTE = \"first\"
+NOTE = \"second\"
+GROUP = STATS
+  MEAN = 12.5 <DN>
+  VALID = TRUE
+END_GROUP = STATS
+END
+";
+    let doc = PvlDocument::parse(label).unwrap();
+    let image: Image = from_document(&doc).unwrap();
+    assert_eq!(
+        image,
+        Image {
+            lines: 1024,
+            samples: 512,
+            band_names: vec!["RED".to_owned(), "GREEN".to_owned()],
+            note: vec!["first".to_owned(), "second".to_owned()],
+            stats: Stats {
+                mean: 12.5,
+                valid: true
+            },
+            target: None,
+        }
+    );
+
+    let doc = PvlDocument::parse("LINES = 1024\nBAND_NAMES = (RED)\nNOTE = \"a\"\nEND\n").unwrap();
+    match from_document::<Image>(&doc) {
+        Err(Error::General(msg)) => assert_eq!(msg, "missing field `SAMPLES`"),
+        other => panic!("Expected a missing field error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_serde_value_to_json_value() {
+    assert_eq!(Value::new("1024").to_json_value(), json!(1024));
+    assert_eq!(Value::new("-3").to_json_value(), json!(-3));
+    assert_eq!(Value::new("0.5 <DN>").to_json_value(), json!(0.5));
+    assert_eq!(Value::new("\"TRUE\"").to_json_value(), json!(true));
+    assert_eq!(
+        Value::new("\"a \"\"b\"\"\"").to_json_value(),
+        json!("a \"b\"")
+    );
+    assert_eq!(Value::new("PDS3").to_json_value(), json!("PDS3"));
+    assert_eq!(
+        Value::new("(1, (2.5, \"X\"), RED)").to_json_value(),
+        json!([1, [2.5, "X"], "RED"])
+    );
+
+    let undetermined = Value::from_typed("1024", ValueType::Undetermined);
+    assert_eq!(undetermined.to_json_value(), json!("1024"));
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+enum Kind {
+    Image,
+    Table,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+struct Product {
+    kind: Kind,
+    kinds: Vec<Kind>,
+    spare: Option<Kind>,
+}
+
+#[test]
+fn test_serde_unit_enum() {
+    let label = "KIND = Image\nKINDS = (Table, \"Image\")\nSPARE = \"Table\"\nEND\n";
+    let doc = PvlDocument::parse(label).unwrap();
+    let product: Product = from_document(&doc).unwrap();
+    assert_eq!(
+        product,
+        Product {
+            kind: Kind::Image,
+            kinds: vec![Kind::Table, Kind::Image],
+            spare: Some(Kind::Table),
+        }
+    );
+
+    let doc = PvlDocument::parse("KIND = CUBE\nKINDS = ()\nEND\n").unwrap();
+    assert!(from_document::<Product>(&doc).is_err());
+}
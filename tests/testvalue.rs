@@ -0,0 +1,677 @@
+use pvl::*;
+
+#[test]
+fn test_value_radix_hex_case() {
+    let upper = Value::new("16#FF#");
+    let lower = Value::new("16#ff#");
+
+    assert_eq!(upper.parse_radix().unwrap(), 255);
+    assert_eq!(lower.parse_radix().unwrap(), 255);
+
+    assert_eq!(upper.to_pvl(), "16#FF#");
+    assert_eq!(lower.to_pvl(), "16#ff#");
+}
+
+#[test]
+fn test_value_radix_signed() {
+    let negative = Value::new("-16#FF#");
+    assert_eq!(negative.value_type(), ValueType::Radix);
+    assert_eq!(negative.parse_radix().unwrap(), -255);
+    assert_eq!(Value::new("+16#FF#").parse_radix().unwrap(), 255);
+    assert_eq!(Value::new("16#FF#").parse_radix().unwrap(), 255);
+    assert_eq!(Value::new("-2#1010#").parse_radix().unwrap(), -10);
+}
+
+#[test]
+fn test_value_degree_units() {
+    for raw in ["45.0 <deg>", "45.0 <DEGREES>", "45.0 <°>"] {
+        let v = Value::new(raw);
+        assert_eq!(v.unit_enum(), Some(ValueUnits::Degrees));
+        assert_eq!(v.parse_f64().unwrap(), 45.0);
+    }
+    assert_eq!("°".parse::<ValueUnits>().unwrap(), ValueUnits::Degrees);
+    assert!("furlongs".parse::<ValueUnits>().is_err());
+}
+
+#[test]
+fn test_value_degree_sign_from_label() {
+    let doc = PvlDocument::parse("ANGLE = 45.0 <°>\nEND\n").unwrap();
+    let v = doc.get("ANGLE").unwrap();
+    assert_eq!(v.units(), Some("°"));
+    assert_eq!(v.unit_enum(), Some(ValueUnits::Degrees));
+}
+
+#[test]
+fn test_value_as_array_fixed() {
+    let v = Value::new("(255, 128, 0)");
+    assert_eq!(v.as_array_fixed::<3>().unwrap(), [255.0, 128.0, 0.0]);
+
+    match v.as_array_fixed::<2>() {
+        Err(Error::General(msg)) => assert!(msg.contains("Expected an array of 2 elements")),
+        other => panic!("Expected a count mismatch error, got {:?}", other),
+    }
+
+    assert!(Value::new("(1.5, A)").as_array_fixed::<2>().is_err());
+}
+
+#[test]
+fn test_value_quote_char() {
+    let quoted = Value::new("\"123\"");
+    assert!(quoted.is_quoted());
+    assert_eq!(quoted.quote_char(), Some('"'));
+
+    let integer = Value::new("123");
+    assert!(!integer.is_quoted());
+    assert_eq!(integer.quote_char(), None);
+
+    assert_eq!(Value::new("'SYMBOL'").quote_char(), Some('\''));
+    assert_eq!(Value::new("\"").quote_char(), None);
+}
+
+#[test]
+fn test_value_parse_string_unquotes() {
+    assert_eq!(Value::new("\"hello\"").parse_string().unwrap(), "hello");
+    assert_eq!(Value::new("\"a\"\"b\"").parse_string().unwrap(), "a\"b");
+    assert_eq!(Value::new("\"\"").parse_string().unwrap(), "");
+}
+
+#[test]
+fn test_value_as_bool_array() {
+    let v = Value::new("(TRUE, FALSE, TRUE)");
+    assert_eq!(v.as_bool_array().unwrap(), vec![true, false, true]);
+
+    assert_eq!(
+        Value::new("(\"TRUE\", \"false\")").as_bool_array().unwrap(),
+        vec![true, false]
+    );
+    assert!(Value::new("(TRUE, MAYBE)").as_bool_array().is_err());
+    assert!(Value::new("TRUE").as_bool_array().is_err());
+}
+
+#[test]
+fn test_value_parse_pointer() {
+    let ptr = Value::new("(\"NRB_0001.IMG\", 5)").parse_pointer().unwrap();
+    assert_eq!(ptr.filename.as_deref(), Some("NRB_0001.IMG"));
+    assert_eq!(ptr.offset, Some(5));
+    assert_eq!(ptr.units, None);
+
+    let ptr = Value::new("12345 <BYTES>").parse_pointer().unwrap();
+    assert_eq!(ptr.filename, None);
+    assert_eq!(ptr.offset, Some(12345));
+    assert_eq!(ptr.units.as_deref(), Some("BYTES"));
+
+    let ptr = Value::new("\"file.img\"").parse_pointer().unwrap();
+    assert_eq!(
+        ptr,
+        Pointer {
+            filename: Some("file.img".to_owned()),
+            offset: None,
+            units: None
+        }
+    );
+
+    assert!(Value::new("(1, 2, 3)").parse_pointer().is_err());
+}
+
+#[test]
+fn test_value_nested_mixed_array() {
+    let v = Value::new("({\"a,b\", 2}, (\"c\", {3}))");
+    let outer = v.parse_array().unwrap();
+    assert_eq!(outer.len(), 2);
+
+    let set = outer[0].parse_array().unwrap();
+    assert_eq!(set.len(), 2);
+    assert_eq!(set[0].parse_string().unwrap(), "a,b");
+    assert_eq!(set[1].parse_u32().unwrap(), 2);
+
+    let sequence = outer[1].parse_array().unwrap();
+    assert_eq!(sequence.len(), 2);
+    assert_eq!(sequence[0].parse_string().unwrap(), "c");
+    let inner = sequence[1].parse_array().unwrap();
+    assert_eq!(inner.len(), 1);
+    assert_eq!(inner[0].parse_u32().unwrap(), 3);
+
+    assert!(Value::new("()").parse_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_value_datetime_keeps_precision() {
+    let v = Value::new("2019-06-20T12:34:56.789Z");
+    let dt = v.parse_datetime().unwrap();
+    assert_eq!(
+        dt.date,
+        Date::Calendar {
+            year: 2019,
+            month: 6,
+            day: 20
+        }
+    );
+    assert_eq!((dt.hour, dt.minute, dt.second), (12, 34, Some(56)));
+    assert_eq!(dt.fraction.as_deref(), Some("789"));
+    assert_eq!(dt.zone.as_deref(), Some("Z"));
+    assert_eq!(dt.to_string(), "2019-06-20T12:34:56.789Z");
+
+    // Trailing zeros and the absence of a zone are kept too
+    let dt = Value::new("2019-171T01:02:03.500")
+        .parse_datetime()
+        .unwrap();
+    assert_eq!(
+        dt.date,
+        Date::DayOfYear {
+            year: 2019,
+            day: 171
+        }
+    );
+    assert_eq!(dt.to_string(), "2019-171T01:02:03.500");
+
+    let doc = PvlDocument::parse("START_TIME = 2019-06-20T12:34:56.789Z\nEND\n").unwrap();
+    assert!(doc
+        .to_pvl_string()
+        .contains("START_TIME = 2019-06-20T12:34:56.789Z\n"));
+}
+
+#[test]
+fn test_value_quoted_datetime() {
+    let quoted = Value::new("\"2014-07-29T12:34:56Z\"");
+    let unquoted = Value::new("2014-07-29T12:34:56Z");
+    assert_eq!(quoted.value_type(), ValueType::String);
+    let quoted_dt = quoted.parse_datetime().unwrap();
+    let unquoted_dt = unquoted.parse_datetime().unwrap();
+    assert!(quoted_dt.quoted);
+    assert!(!unquoted_dt.quoted);
+    assert_eq!(
+        DateTime {
+            quoted: false,
+            ..quoted_dt.clone()
+        },
+        unquoted_dt
+    );
+    assert_eq!(quoted_dt.to_string(), "\"2014-07-29T12:34:56Z\"");
+    assert_eq!(unquoted_dt.to_string(), "2014-07-29T12:34:56Z");
+    assert!(quoted.is_quoted());
+    assert!(!unquoted.is_quoted());
+    assert_eq!(quoted.to_pvl(), "\"2014-07-29T12:34:56Z\"");
+
+    assert!(matches!(
+        Value::new("\"not a time\"").parse_datetime(),
+        Err(Error::ValueTypeParseError)
+    ));
+
+    // Quoted dates and times parse the same way
+    assert_eq!(
+        Value::new("\"12:34:56\"").parse_time().unwrap(),
+        Value::new("12:34:56").parse_time().unwrap()
+    );
+    assert_eq!(
+        Value::new("\"2014-210\"").parse_date().unwrap(),
+        Value::new("2014-210").parse_date().unwrap()
+    );
+    assert!(matches!(
+        Value::new("12").parse_time(),
+        Err(Error::InvalidType)
+    ));
+}
+
+#[test]
+fn test_value_float_overflow() {
+    let v = Value::new("1e400");
+    assert!(matches!(v.parse_f64(), Err(Error::Overflow)));
+    assert!(matches!(
+        Value::new("1.0e39").parse_f32(),
+        Err(Error::Overflow)
+    ));
+    assert_eq!(Value::new("1.5e3").parse_f64().unwrap(), 1500.0);
+}
+
+#[test]
+fn test_value_typed_arrays() {
+    let floats = Value::new("(1.0, 2.0, 3.0)");
+    assert_eq!(floats.parse_f64_array().unwrap(), vec![1.0, 2.0, 3.0]);
+    assert!(matches!(floats.parse_i64_array(), Err(Error::InvalidType)));
+
+    let ints = Value::new("(1, -2, 3)");
+    assert_eq!(ints.parse_i64_array().unwrap(), vec![1, -2, 3]);
+    assert_eq!(ints.parse_f64_array().unwrap(), vec![1.0, -2.0, 3.0]);
+
+    let names = Value::new("(RED, \"NEAR INFRARED\")");
+    assert_eq!(
+        names.parse_string_array().unwrap(),
+        vec!["RED".to_owned(), "NEAR INFRARED".to_owned()]
+    );
+
+    let mixed = Value::new("(1.0, \"TWO\", 3.0)");
+    assert!(mixed.parse_f64_array().is_err());
+    assert!(mixed.parse_string_array().is_err());
+    assert!(Value::new("1.0").parse_f64_array().is_err());
+}
+
+#[test]
+fn test_value_flatten_numeric() {
+    let matrix = Value::new("((1,2),(3,4))");
+    assert_eq!(matrix.flatten_numeric().unwrap(), vec![1.0, 2.0, 3.0, 4.0]);
+    assert_eq!(
+        Value::new("(1.5, {2, (3)})").flatten_numeric().unwrap(),
+        vec![1.5, 2.0, 3.0]
+    );
+    assert!(Value::new("((1, 2), (3, FOUR))").flatten_numeric().is_err());
+}
+
+#[test]
+fn test_value_parse_bitmask() {
+    assert_eq!(Value::new("8#00001111#").parse_bitmask().unwrap(), 15);
+    assert_eq!(Value::new("#101#").parse_bitmask().unwrap(), 5);
+    assert!(matches!(
+        Value::new("4#00001111#").parse_bitmask(),
+        Err(Error::Overflow)
+    ));
+    assert!(matches!(
+        Value::new("16#FF#").parse_bitmask(),
+        Err(Error::InvalidType)
+    ));
+}
+
+#[test]
+fn test_value_equality() {
+    let a = Value::new("5");
+    assert_eq!(a, Value::new("5"));
+    assert_eq!(a.clone(), a);
+    assert_ne!(a, Value::new("5.0"));
+    assert_ne!(a, Value::new("\"5\""));
+
+    let kvp = KeyValuePair::new(Symbol::Key("LINES".to_owned()), a.clone());
+    assert_eq!(kvp.clone(), kvp);
+}
+
+#[test]
+fn test_value_display() {
+    assert_eq!(Value::new(" 1024 ").to_string(), "1024");
+    assert_eq!(Value::new("12.5 <DEGREES>").to_string(), "12.5 <DEGREES>");
+    assert_eq!(
+        Value::new("\"SAY \"\"HI\"\"\"").to_string(),
+        "\"SAY \"\"HI\"\"\""
+    );
+    assert_eq!(Value::new("(1,2,  3)").to_string(), "(1, 2, 3)");
+    assert_eq!(
+        Value::new("((RED,\"NEAR INFRARED\"),{1,2})").to_string(),
+        "((RED, \"NEAR INFRARED\"), {1, 2})"
+    );
+    assert_eq!(format!("{}", Value::new("IMAGE")), "IMAGE");
+}
+
+#[test]
+fn test_value_parse_time() {
+    let short = Value::new("14:32");
+    assert_eq!(short.value_type(), ValueType::Time);
+    let t = short.parse_time().unwrap();
+    assert_eq!((t.hour, t.minute, t.second), (14, 32, 0));
+
+    let t = Value::new("14:32:05").parse_time().unwrap();
+    assert_eq!((t.hour, t.minute, t.second), (14, 32, 5));
+    assert_eq!(t.fraction, None);
+
+    let t = Value::new("14:32:05.25Z").parse_time().unwrap();
+    assert_eq!(t.fraction.as_deref(), Some("25"));
+    assert_eq!(t.zone.as_deref(), Some("Z"));
+}
+
+#[test]
+fn test_value_type_classify_and_from_str() {
+    assert_eq!(ValueType::classify("3.14"), ValueType::Float);
+    assert_eq!(ValueType::classify("42"), ValueType::Integer);
+    assert_eq!(ValueType::classify("(1, 2)"), ValueType::Array);
+    assert_eq!("Integer".parse::<ValueType>().unwrap(), ValueType::Integer);
+    assert_eq!(
+        "datetime".parse::<ValueType>().unwrap(),
+        ValueType::DateTime
+    );
+    assert!("Number".parse::<ValueType>().is_err());
+}
+
+#[test]
+fn test_value_from_typed() {
+    let v = Value::from_typed("1024", ValueType::String);
+    assert_eq!(v.value_type(), ValueType::String);
+    assert_eq!(v.as_str(), "1024");
+    assert_eq!(v.parse_string().unwrap(), "1024");
+    assert!(matches!(v.parse_i64(), Err(Error::InvalidType)));
+    assert_ne!(v, Value::new("1024"));
+    assert_eq!(Value::new("1024").value_type(), ValueType::Integer);
+}
+
+#[test]
+fn test_value_from_typed_array_unbracketed() {
+    for text in ["", "x", "(", "(1, 2}", "1, 2"] {
+        let v = Value::from_typed(text, ValueType::Array);
+        assert!(matches!(v.parse_array(), Err(Error::InvalidType)));
+        assert!(v.parse_f64_array().is_err());
+        // Falls back to the text as written
+        assert_eq!(v.to_string(), text);
+    }
+    assert_eq!(
+        Value::from_typed("{A, B}", ValueType::Array)
+            .parse_array()
+            .unwrap()
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn test_value_f64_array_nan() {
+    let v = Value::new("(1.0, N/A, 3.0)").as_f64_array_nan().unwrap();
+    assert_eq!(v.len(), 3);
+    assert_eq!(v[0], 1.0);
+    assert!(v[1].is_nan());
+    assert_eq!(v[2], 3.0);
+
+    let v = Value::new("(UNK, \"NULL\", 2)").as_f64_array_nan().unwrap();
+    assert!(v[0].is_nan() && v[1].is_nan());
+    assert_eq!(v[2], 2.0);
+
+    assert!(Value::new("(1.0, N/A, 3.0)").parse_f64_array().is_err());
+    assert!(Value::new("(1.0, NONE)").as_f64_array_nan().is_err());
+}
+
+#[test]
+fn test_value_max_nesting_depth() {
+    let deep = Value::new(&format!("{}1{}", "(".repeat(1000), ")".repeat(1000)));
+    match deep.parse_array() {
+        Err(Error::Syntax(why)) => assert_eq!(why, "max nesting depth exceeded"),
+        other => panic!("Expected a syntax error, got {:?}", other),
+    }
+    assert!(deep.flatten_numeric().is_err());
+
+    let shallow = Value::new("((1, 2), (3))");
+    assert!(shallow.parse_array_with_max_depth(2).is_ok());
+    assert!(shallow.parse_array_with_max_depth(1).is_err());
+}
+
+#[test]
+fn test_value_parse_range() {
+    assert_eq!(Value::new("10:20").parse_range().unwrap(), (10.0, 20.0));
+    assert_eq!(Value::new("(1..5)").parse_range().unwrap(), (1.0, 5.0));
+    assert_eq!(
+        Value::new("-2.5 .. 2.5").parse_range().unwrap(),
+        (-2.5, 2.5)
+    );
+    assert_eq!(Value::new("(0, 255)").parse_range().unwrap(), (0.0, 255.0));
+    assert!(Value::new("(0, 1, 2)").parse_range().is_err());
+    assert!(Value::new("LOW:HIGH").parse_range().is_err());
+}
+
+#[test]
+fn test_value_try_as_bool_or_flag() {
+    assert_eq!(
+        Value::new("TRUE").try_as_bool_or_flag().unwrap(),
+        BoolOrFlag::Bool(true)
+    );
+    assert_eq!(
+        Value::new("\"FALSE\"").try_as_bool_or_flag().unwrap(),
+        BoolOrFlag::Bool(false)
+    );
+    assert_eq!(
+        Value::new("false").try_as_bool_or_flag().unwrap(),
+        BoolOrFlag::Bool(false)
+    );
+    assert_eq!(
+        Value::new("PARTIAL").try_as_bool_or_flag().unwrap(),
+        BoolOrFlag::Flag("PARTIAL".to_owned())
+    );
+    assert!(Value::new("12").try_as_bool_or_flag().is_err());
+    assert!(Value::new("\"PARTIAL\"").try_as_bool_or_flag().is_err());
+}
+
+#[test]
+fn test_value_from_str() {
+    let value = "1024 <BYTES>".parse::<Value>().unwrap();
+    assert_eq!(value.value_type(), ValueType::Integer);
+    assert_eq!(value, Value::new("1024 <BYTES>"));
+    assert_eq!(
+        "(1, 2)".parse::<Value>().unwrap().value_type(),
+        ValueType::Array
+    );
+
+    assert!(matches!(
+        "???".parse::<Value>(),
+        Err(Error::ValueTypeParseError)
+    ));
+    assert_eq!(Value::new("???").value_type(), ValueType::Undetermined);
+}
+
+#[test]
+fn test_value_as_duration() {
+    use std::time::Duration;
+
+    assert_eq!(
+        Value::new("1500 <ms>").as_duration().unwrap(),
+        Duration::from_millis(1500)
+    );
+    assert_eq!(
+        Value::new("2 <s>").as_duration().unwrap(),
+        Duration::from_secs(2)
+    );
+    assert_eq!(
+        Value::new("0.25 <SECONDS>").as_duration().unwrap(),
+        Duration::from_millis(250)
+    );
+    assert!(matches!(
+        Value::new("90 <deg>").as_duration(),
+        Err(Error::InvalidType)
+    ));
+    assert!(matches!(
+        Value::new("1500").as_duration(),
+        Err(Error::InvalidType)
+    ));
+    assert!(Value::new("-1 <s>").as_duration().is_err());
+}
+
+#[test]
+fn test_value_unknown_units() {
+    let v = Value::new("12 <furlongs>");
+    assert_eq!(v.raw_without_unit(), "12");
+    assert_eq!(v.unit_raw(), Some("furlongs"));
+    assert_eq!(v.unit_enum(), None);
+    assert_eq!(v.parse_u32().unwrap(), 12);
+
+    let v = Value::new("1.5E-3 < W/m**2/sr/um >");
+    assert_eq!(v.raw_without_unit(), "1.5E-3");
+    assert_eq!(v.unit_raw(), Some("W/m**2/sr/um"));
+    assert_eq!(v.parse_f64().unwrap(), 1.5E-3);
+
+    let v = Value::new("1024");
+    assert_eq!(v.raw_without_unit(), "1024");
+    assert_eq!(v.unit_raw(), None);
+}
+
+#[test]
+fn test_value_compound_units() {
+    let v = Value::new("9.8 <M/S**2>");
+    assert_eq!(v.value_type(), ValueType::Float);
+    assert_eq!(v.units(), Some("M/S**2"));
+    assert_eq!(v.unit_enum(), None);
+    assert_eq!(v.parse_f64().unwrap(), 9.8);
+
+    let v = Value::new("1500.25 <KM>");
+    assert_eq!(v.units(), Some("KM"));
+    assert_eq!(v.unit_enum(), None);
+    assert_eq!(v.parse_f64().unwrap(), 1500.25);
+
+    let v = Value::new("(1.0, 2.0) <M/S>");
+    assert_eq!(v.units(), Some("M/S"));
+}
+
+#[test]
+fn test_value_surrounding_whitespace() {
+    let v = Value::new("  1024  ");
+    assert_eq!(v.value_type(), ValueType::Integer);
+    assert_eq!(v.parse_u32().unwrap(), 1024);
+    assert_eq!(v.as_str(), "  1024  ");
+
+    for (raw, value_type) in [
+        (" 3.5\t", ValueType::Float),
+        ("  \"TRUE\" ", ValueType::Bool),
+        (" \"text\"  ", ValueType::String),
+        (" (1, 2) ", ValueType::Array),
+        (" 2019-06-20T12:34:56Z ", ValueType::DateTime),
+        (" 16#FF# ", ValueType::Radix),
+        (" PDS3 ", ValueType::Flag),
+    ] {
+        assert_eq!(Value::new(raw).value_type(), value_type, "{:?}", raw);
+        assert_eq!(
+            Value::new(raw).value_type(),
+            Value::new(raw.trim()).value_type()
+        );
+    }
+    assert_eq!(Value::new(" 12.5 <DN> ").parse_f64().unwrap(), 12.5);
+    assert!(Value::new(" \"TRUE\" ").as_bool().unwrap());
+}
+
+#[test]
+fn test_value_type_predicates() {
+    assert!(Value::new("1024").is_integer());
+    assert!(!Value::new("1024").is_float());
+    assert!(Value::new("1.5E3").is_float());
+    assert!(Value::new("\"text\"").is_string());
+    assert!(Value::new("\"TRUE\"").is_bool());
+    assert!(!Value::new("\"TRUE\"").is_string());
+    assert!(Value::new("(1, 2)").is_array());
+    assert!(Value::new("PDS3").is_flag());
+    assert!(Value::new("8#00001111#").is_bitmask());
+    assert!(!Value::new("???").is_flag());
+}
+
+#[test]
+fn test_value_parse_date() {
+    let calendar = Value::new("2014-07-29");
+    assert_eq!(calendar.value_type(), ValueType::Date);
+    assert_eq!(
+        calendar.parse_date().unwrap(),
+        Date::Calendar {
+            year: 2014,
+            month: 7,
+            day: 29
+        }
+    );
+
+    let doy = Value::new("2014-210");
+    assert_eq!(doy.value_type(), ValueType::Date);
+    assert_eq!(
+        doy.parse_date().unwrap(),
+        Date::DayOfYear {
+            year: 2014,
+            day: 210
+        }
+    );
+    assert_eq!(doy.parse_date().unwrap().to_string(), "2014-210");
+
+    assert_eq!(
+        Value::new("\"2014-07-29\"").parse_date().unwrap(),
+        calendar.parse_date().unwrap()
+    );
+    assert_eq!(
+        Value::new("2014-07-29T12:00:00").value_type(),
+        ValueType::DateTime
+    );
+    assert!(matches!(
+        Value::new("2014-07-29T12:00:00").parse_date(),
+        Err(Error::InvalidType)
+    ));
+    assert!(calendar.parse_datetime().is_err());
+    assert_eq!("date".parse::<ValueType>().unwrap(), ValueType::Date);
+}
+
+#[test]
+fn test_value_flag_underscores() {
+    for flag in [
+        "ROVER_NAV_CAM",
+        "MODE_1",
+        "A_B_C",
+        "FILTER_1_LEFT",
+        "LEVEL_2_",
+    ] {
+        assert_eq!(Value::new(flag).value_type(), ValueType::Flag, "{}", flag);
+    }
+    assert_ne!(Value::new("1_MODE").value_type(), ValueType::Flag);
+}
+
+#[test]
+fn test_value_non_finite_floats() {
+    for text in ["inf", "-Infinity", "NaN"] {
+        let v = Value::from_typed(text, ValueType::Float);
+        assert!(matches!(v.parse_f64(), Err(Error::ValueTypeParseError)));
+        assert!(matches!(v.parse_f32(), Err(Error::ValueTypeParseError)));
+        assert!(!v.parse_f64_with_non_finite(true).unwrap().is_finite());
+    }
+    assert_eq!(
+        Value::from_typed("-inf", ValueType::Float)
+            .parse_f64_with_non_finite(true)
+            .unwrap(),
+        f64::NEG_INFINITY
+    );
+    assert!(Value::from_typed("NaN", ValueType::Float)
+        .parse_f32_with_non_finite(true)
+        .unwrap()
+        .is_nan());
+
+    // Too large to represent isn't the same as spelled out
+    assert!(matches!(
+        Value::new("1e400").parse_f64_with_non_finite(true),
+        Err(Error::Overflow)
+    ));
+    assert_eq!(
+        Value::new("2.5").parse_f64_with_non_finite(false).unwrap(),
+        2.5
+    );
+}
+
+#[test]
+fn test_value_from_f64() {
+    for (v, text) in [
+        (5.0, "5.0"),
+        (-0.25, "-0.25"),
+        (0.00001, "1.0E-5"),
+        (1.5e300, "1.5E300"),
+        (123456.0, "123456.0"),
+    ] {
+        let value = Value::from_f64(v).unwrap();
+        assert_eq!(value.to_pvl(), text);
+        assert_eq!(Value::from(v).to_pvl(), text);
+        assert_eq!(value.value_type(), ValueType::Float);
+
+        let reparsed = Value::new(&value.to_pvl());
+        assert_eq!(reparsed.value_type(), ValueType::Float, "{}", text);
+        assert_eq!(reparsed.parse_f64().unwrap(), v);
+    }
+    for v in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        assert!(matches!(
+            Value::from_f64(v),
+            Err(Error::ValueTypeParseError)
+        ));
+        assert_eq!(Value::from(v).value_type(), ValueType::Undetermined);
+    }
+    assert_eq!(Value::from(1e-7).to_pvl(), "1.0E-7");
+}
+
+#[test]
+fn test_value_was_quoted() {
+    let doc = PvlDocument::parse(
+        "QUOTED = \"BAR\"\nFLAG = BAR\nQUOTED_BOOL = \"TRUE\"\nBOOL = TRUE\nNUMBER = 12\nEND\n",
+    )
+    .unwrap();
+    let quoted = doc.get("QUOTED").unwrap();
+    let flag = doc.get("FLAG").unwrap();
+    assert_eq!(quoted.value_type(), ValueType::String);
+    assert_eq!(flag.value_type(), ValueType::Flag);
+    assert!(quoted.was_quoted());
+    assert!(!flag.was_quoted());
+
+    assert!(doc.get("QUOTED_BOOL").unwrap().was_quoted());
+    assert!(!doc.get("BOOL").unwrap().was_quoted());
+    assert!(!doc.get("NUMBER").unwrap().was_quoted());
+    assert!(!Value::new("\"").was_quoted());
+
+    // A symbol string is quoted, but not in double quotes
+    let symbol = Value::new("'BAR'");
+    assert!(symbol.is_quoted());
+    assert!(!symbol.was_quoted());
+}
@@ -0,0 +1,163 @@
+use pvl::*;
+
+#[test]
+fn test_writer_preserves_block_kind() {
+    let doc = PvlDocument::parse(
+        "GROUP = PROCESSING
+  VERSION = 2
+END_GROUP = PROCESSING
+OBJECT = IMAGE
+  LINES = 1024
+END_OBJECT = IMAGE
+END
+",
+    )
+    .unwrap();
+
+    let text = doc.to_pvl_string();
+    assert!(text.contains("GROUP = PROCESSING\n"));
+    assert!(text.contains("END_GROUP = PROCESSING\n"));
+    assert!(text.contains("OBJECT = IMAGE\n"));
+    assert!(text.contains("END_OBJECT = IMAGE\n"));
+    assert!(!text.contains("GROUP = IMAGE"));
+
+    let reparsed = PvlDocument::parse(&text).unwrap();
+    let kinds = reparsed
+        .nodes
+        .iter()
+        .filter_map(|n| match n {
+            PvlNode::Block(g) => Some((g.name.as_str(), g.kind)),
+            _ => None,
+        })
+        .collect::<Vec<(&str, BlockKind)>>();
+    assert_eq!(
+        kinds,
+        vec![
+            ("PROCESSING", BlockKind::Group),
+            ("IMAGE", BlockKind::Object)
+        ]
+    );
+}
+
+/// Asserts two documents hold the same blocks and values, comparing arrays element by element
+/// so differences in how they were wrapped don't matter
+fn assert_equivalent(a: &PvlDocument, b: &PvlDocument) {
+    assert_eq!(a.nodes.len(), b.nodes.len());
+    for (x, y) in a.nodes.iter().zip(b.nodes.iter()) {
+        match (x, y) {
+            (PvlNode::Pair(x), PvlNode::Pair(y)) => {
+                assert_eq!(x.key, y.key);
+                assert_values_equivalent(&x.value, &y.value);
+            }
+            (PvlNode::Block(x), PvlNode::Block(y)) => {
+                assert_eq!((x.kind, &x.name), (y.kind, &y.name));
+                assert_equivalent(&x.document, &y.document);
+            }
+            _ => panic!("Mismatched nodes {:?} and {:?}", x, y),
+        }
+    }
+}
+
+fn assert_values_equivalent(a: &Value, b: &Value) {
+    match (a.parse_array(), b.parse_array()) {
+        (Ok(x), Ok(y)) => {
+            assert_eq!(x.len(), y.len());
+            for (x, y) in x.iter().zip(y.iter()) {
+                assert_values_equivalent(x, y);
+            }
+        }
+        _ => assert_eq!(a.to_pvl(), b.to_pvl()),
+    }
+}
+
+#[test]
+fn test_writer_round_trip_fixture() {
+    let doc = PvlDocument::from_file("tests/testdata/cassini/vims/v1883935188_1.lbl").unwrap();
+    let text = doc.to_pvl_string();
+    assert!(text.ends_with("END\n"));
+    assert!(text.lines().all(|l| l.len() <= 80 || !l.contains('(')));
+    assert_equivalent(&doc, &PvlDocument::parse(&text).unwrap());
+}
+
+#[test]
+fn test_writer_options() {
+    let doc = PvlDocument::parse(
+        "OBJECT = IMAGE
+  FILTER_NAMES = (RED, GREEN, BLUE, \"NEAR INFRARED\", \"ULTRA VIOLET\")
+END_OBJECT = IMAGE
+END
+",
+    )
+    .unwrap();
+    let options = WriterOptions {
+        indent: 4,
+        continuation_column: 20,
+        line_width: 40,
+        ..Default::default()
+    };
+    let text = doc.to_pvl_string_with(&options);
+    assert!(text.contains("\n    FILTER_NAMES = (RED, GREEN, BLUE,\n"));
+    assert!(text.lines().all(|l| l.len() <= 40));
+    assert!(text
+        .lines()
+        .filter(|l| !l.contains('='))
+        .all(|l| l.starts_with(&" ".repeat(20)) || l == "END"));
+
+    let mut reader = PvlReader::new(&text);
+    reader.set_continuation_indent(20);
+    assert_equivalent(&doc, &reader.parse_document().unwrap());
+}
+
+#[test]
+fn test_writer_pretty_blank_lines() {
+    let doc = PvlDocument::parse(
+        "PDS_VERSION_ID = PDS3
+GROUP = PROCESSING
+  VERSION = 2
+END_GROUP = PROCESSING
+OBJECT = IMAGE
+  LINES = 1024
+  GROUP = STATS
+    MEAN = 12.5
+  END_GROUP = STATS
+END_OBJECT = IMAGE
+END
+",
+    )
+    .unwrap();
+
+    let text = doc.to_pretty_string();
+    assert!(text.starts_with("PDS_VERSION_ID = PDS3\n\nGROUP = PROCESSING\n"));
+    assert!(text.contains("END_GROUP = PROCESSING\n\nOBJECT = IMAGE\n"));
+    assert!(text.contains("LINES = 1024\n\n  GROUP = STATS\n"));
+    assert!(!doc.to_pvl_string().contains("\n\n"));
+    assert_equivalent(&doc, &PvlDocument::parse(&text).unwrap());
+}
+
+#[test]
+fn test_writer_pointers_first() {
+    let doc = PvlDocument::parse(
+        "PDS_VERSION_ID = PDS3
+RECORD_BYTES = 512
+^IMAGE = 3
+OBJECT = IMAGE
+  LINES = 1024
+  ^TABLE = 7
+END_OBJECT = IMAGE
+^HEADER = 2
+END
+",
+    )
+    .unwrap();
+
+    let options = WriterOptions {
+        pointers_first: true,
+        ..Default::default()
+    };
+    let text = doc.to_pvl_string_with(&options);
+    assert!(
+        text.starts_with("^IMAGE = 3\n^HEADER = 2\nPDS_VERSION_ID = PDS3\nRECORD_BYTES = 512\n")
+    );
+    assert!(text.contains("OBJECT = IMAGE\n  ^TABLE = 7\n  LINES = 1024\n"));
+    assert!(doc.to_pvl_string().starts_with("PDS_VERSION_ID = PDS3\n"));
+}